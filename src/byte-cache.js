@@ -0,0 +1,82 @@
+// 按总字节数限制容量的 LRU 缓存：超出容量时淘汰最久未使用的条目，
+// 单个值超过容量时不缓存。sizeOf 计算值占用的字节数（默认支持 Buffer 和字符串）
+class ByteLruCache {
+  constructor(maxBytes, sizeOf = ByteLruCache.defaultSizeOf) {
+    this.maxBytes = maxBytes;
+    this.sizeOf = sizeOf;
+    this.entries = new Map();
+    this.bytes = 0;
+    // 命中 / 未命中次数，用于统计缓存效果
+    this.hits = 0;
+    this.misses = 0;
+  }
+
+  get size() {
+    return this.entries.size;
+  }
+
+  has(key) {
+    return this.entries.has(key);
+  }
+
+  // 读取并标记为最近使用，没有时返回 undefined
+  get(key) {
+    if (!this.entries.has(key)) {
+      this.misses++;
+      return undefined;
+    }
+    this.hits++;
+    const entry = this.entries.get(key);
+    this.entries.delete(key);
+    this.entries.set(key, entry);
+    return entry.value;
+  }
+
+  // 写入缓存，返回是否缓存成功
+  set(key, value) {
+    const size = this.sizeOf(value);
+    this.delete(key);
+    if (size > this.maxBytes) {
+      return false;
+    }
+
+    this.entries.set(key, { value, size });
+    this.bytes += size;
+    this.evict();
+    return true;
+  }
+
+  delete(key) {
+    if (this.entries.has(key)) {
+      this.bytes -= this.entries.get(key).size;
+      this.entries.delete(key);
+    }
+  }
+
+  clear() {
+    this.entries.clear();
+    this.bytes = 0;
+  }
+
+  // 修改容量，缩小时立即淘汰多出的条目
+  setMaxBytes(maxBytes) {
+    this.maxBytes = maxBytes;
+    this.evict();
+  }
+
+  evict() {
+    for (const [key, entry] of this.entries) {
+      if (this.bytes <= this.maxBytes) {
+        break;
+      }
+      this.entries.delete(key);
+      this.bytes -= entry.size;
+    }
+  }
+}
+
+ByteLruCache.defaultSizeOf = function(value) {
+  return typeof value === 'string' ? Buffer.byteLength(value) : value.length;
+};
+
+module.exports = ByteLruCache;
@@ -0,0 +1,60 @@
+// 剪贴板监听的过滤规则：只有看起来像查询词的文字才弹出查询窗口，
+// 复制代码、网址、路径、整段文字或数字时忽略
+const DEFAULT_OPTIONS = {
+  maxWords: 3,
+  maxLength: 40,
+  minLetterRatio: 0.8
+};
+
+const URL_PATTERN = /^[a-z][a-z0-9+.-]*:\/\/|^www\.|^mailto:/i;
+const EMAIL_PATTERN = /^[^\s@]+@[^\s@]+\.[^\s@]+$/;
+// Windows 盘符路径、UNC 路径、Unix 绝对路径或 ~/、./ 开头的相对路径
+const PATH_PATTERN = /^[a-z]:[\\/]|^\\\\|^~?\/|^\.{1,2}[\\/]/i;
+// 代码常见的符号：括号、分号、赋值、箭头、下划线标识符等
+const CODE_PATTERN = /[{}()[\];<>=|\\/`$#@_*^~]/;
+// 字母（包括汉字、假名等各种文字）和组合附加符号
+const LETTER_PATTERN = /[\p{L}\p{M}]/gu;
+// 词语之间允许出现的其它字符：空白、连字符、撇号、句点（缩写 e.g.、U.S.）
+const ALLOWED_PATTERN = /^[\p{L}\p{M}\p{N}\s\-'’.]+$/u;
+
+// 判断文字是否像查询词，返回 { accept, reason }，reason 说明拒绝的原因（接受时为 null）。
+// text 应已去掉首尾的标点；lastQuery 为上一次由剪贴板触发的查询
+function classifyClipboardText(text, options = {}, lastQuery = null) {
+  const { maxWords, maxLength, minLetterRatio } = { ...DEFAULT_OPTIONS, ...options };
+  const reject = reason => ({ accept: false, reason });
+
+  if (!text) {
+    return reject('empty');
+  }
+  if (text.length > maxLength) {
+    return reject('too-long');
+  }
+  if (text.split(/\s+/).length > maxWords) {
+    return reject('too-many-words');
+  }
+  if (URL_PATTERN.test(text) || EMAIL_PATTERN.test(text)) {
+    return reject('url');
+  }
+  if (PATH_PATTERN.test(text)) {
+    return reject('path');
+  }
+  if (CODE_PATTERN.test(text) || !ALLOWED_PATTERN.test(text)) {
+    return reject('symbols');
+  }
+
+  // 字母在字母和数字中所占的比例，连字符、撇号等不计入
+  const letters = (text.match(LETTER_PATTERN) || []).length;
+  const digits = (text.match(/\p{N}/gu) || []).length;
+  if (letters === 0 || letters / (letters + digits) < minLetterRatio) {
+    return reject('not-letters');
+  }
+  if (lastQuery && text.toLowerCase() === lastQuery.toLowerCase()) {
+    return reject('repeated');
+  }
+  return { accept: true, reason: null };
+}
+
+module.exports = {
+  DEFAULT_OPTIONS,
+  classifyClipboardText
+};
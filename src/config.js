@@ -0,0 +1,292 @@
+const fs = require('fs');
+const MdictParser = require('./mdict-parser');
+const { PROVIDERS, FreeDictionaryProvider } = require('./online-providers');
+const Profiles = require('./profiles');
+
+// 快捷键修饰键的规范写法和顺序
+const MODIFIER_ALIASES = {
+  ctrl: 'Ctrl',
+  control: 'Ctrl',
+  alt: 'Alt',
+  option: 'Alt',
+  shift: 'Shift',
+  cmd: 'Cmd',
+  command: 'Cmd',
+  meta: 'Cmd',
+  super: 'Cmd'
+};
+const MODIFIER_ORDER = ['Ctrl', 'Alt', 'Shift', 'Cmd'];
+
+// 主键名称（包括 KeyboardEvent.code 的写法）对应的 Electron 加速键名称
+const KEY_NAMES = {
+  semicolon: ';',
+  backquote: '`',
+  minus: '-',
+  equal: '=',
+  bracketleft: '[',
+  bracketright: ']',
+  comma: ',',
+  period: '.',
+  slash: '/',
+  backslash: '\\',
+  quote: "'",
+  plus: 'Plus',
+  up: 'Up',
+  arrowup: 'Up',
+  down: 'Down',
+  arrowdown: 'Down',
+  left: 'Left',
+  arrowleft: 'Left',
+  right: 'Right',
+  arrowright: 'Right',
+  home: 'Home',
+  end: 'End',
+  pageup: 'PageUp',
+  pagedown: 'PageDown',
+  insert: 'Insert',
+  delete: 'Delete',
+  backspace: 'Backspace',
+  space: 'Space',
+  tab: 'Tab',
+  enter: 'Enter',
+  return: 'Enter',
+  escape: 'Escape',
+  esc: 'Escape',
+  numpadadd: 'numadd',
+  numadd: 'numadd',
+  numpadsubtract: 'numsub',
+  numsub: 'numsub',
+  numpadmultiply: 'nummult',
+  nummult: 'nummult',
+  numpaddivide: 'numdiv',
+  numdiv: 'numdiv',
+  numpaddecimal: 'numdec',
+  numdec: 'numdec'
+};
+
+// 规范化主键名称，不支持的键返回 null
+function canonicalizeKey(key) {
+  const lower = key.toLowerCase();
+  if (KEY_NAMES[lower]) {
+    return KEY_NAMES[lower];
+  }
+  if (Object.values(KEY_NAMES).includes(key)) {
+    return key;
+  }
+
+  const match = lower.match(/^(?:key|digit)?([a-z0-9])$/) ||
+    lower.match(/^(f(?:[1-9]|1\d|2[0-4]))$/) ||
+    lower.match(/^(?:numpad|num)(\d)$/);
+  if (!match) {
+    return null;
+  }
+  if (/^(numpad|num)\d$/.test(lower)) {
+    return `num${match[1]}`;
+  }
+  return match[1].toUpperCase();
+}
+
+// 规范化快捷键写法，如 "shift + ctrl + d" -> "Ctrl+Shift+D"，没有主键或主键不支持时返回 null
+function canonicalizeHotkey(hotkey) {
+  if (typeof hotkey !== 'string') {
+    return null;
+  }
+
+  const modifiers = new Set();
+  let key = null;
+  let valid = true;
+  hotkey.split('+').map(p => p.trim()).filter(Boolean).forEach(part => {
+    const modifier = MODIFIER_ALIASES[part.toLowerCase()];
+    if (modifier) {
+      modifiers.add(modifier);
+    } else {
+      key = canonicalizeKey(part);
+      valid = valid && key !== null;
+    }
+  });
+
+  if (!key || !valid) {
+    return null;
+  }
+  return [...MODIFIER_ORDER.filter(m => modifiers.has(m)), key].join('+');
+}
+
+// 转换为 Electron 的加速键字符串，无法解析时返回 null
+function hotkeyToAccelerator(hotkey) {
+  const canonical = canonicalizeHotkey(hotkey);
+  if (!canonical) {
+    return null;
+  }
+  return canonical.split('+').map(part => {
+    if (part === 'Ctrl' || part === 'Cmd') {
+      return 'CommandOrControl';
+    }
+    return part;
+  }).join('+');
+}
+
+function clampNumber(value, min, max, fallback) {
+  const number = parseFloat(value);
+  if (isNaN(number)) {
+    return fallback;
+  }
+  return Math.min(Math.max(number, min), max);
+}
+
+// 每个配置项的校验规则：返回修正后的值
+const FIELD_RULES = {
+  hotkey: value => canonicalizeHotkey(value) || 'Alt+M',
+  cycleDictionaryHotkey: value => canonicalizeHotkey(value) || '',
+  hotkeyTarget: value => (['lookup', 'main'].includes(value) ? value : 'lookup'),
+  fontFamily: value => (typeof value === 'string' && value.trim() && !/[;{}<>'"]/.test(value) ? value.trim() : 'Segoe UI'),
+  fontSize: value => String(Math.round(clampNumber(value, 10, 32, 14))),
+  lineHeight: value => String(clampNumber(value, 1, 3, 1.6)),
+  fontFallbacks: value => MdictParser.normalizeFontFallbacks(value),
+  renderCacheBytes: value => Math.round(clampNumber(value, 0, 512 * 1024 * 1024, 8 * 1024 * 1024)),
+  resourceCacheBytes: value => Math.round(clampNumber(value, 0, 1024 * 1024 * 1024, 32 * 1024 * 1024)),
+  maxRenderBytes: value => Math.round(clampNumber(value, 0, 64 * 1024 * 1024, 512 * 1024)),
+  redirectBannerText: value => (typeof value === 'string' ? value : '(redirected from "{word}")'),
+  preferredRegion: value => (['auto', 'us', 'uk'].includes(value) ? value : 'auto'),
+  theme: value => (['light', 'dark', 'custom'].includes(value) ? value : 'light'),
+  customTheme: value => MdictParser.normalizeCustomTheme(value),
+  fuzzyDistanceDivisor: value => Math.round(clampNumber(value, 1, 10, 4)),
+  fuzzyDistanceCap: value => Math.round(clampNumber(value, 1, 5, 3)),
+  onlineProvider: value => (PROVIDERS[value] ? value : 'freeDictionary'),
+  onlineLanguage: value => (FreeDictionaryProvider.LANGUAGES.includes(value) ? value : 'en'),
+  onlineCacheTtlHours: value => clampNumber(value, 0, 24 * 365, 168),
+  onlineTimeoutSeconds: value => clampNumber(value, 1, 120, 10),
+  onlineMaxConcurrent: value => Math.round(clampNumber(value, 1, 10, 2)),
+  historySize: value => Math.round(clampNumber(value, 1, 10000, 100)),
+  briefLength: value => Math.round(clampNumber(value, 0, 300, 80)),
+  queryTrimChars: value => (typeof value === 'string' ? value : MdictParser.DEFAULT_QUERY_TRIM_CHARS),
+  clipboardMaxWords: value => Math.round(clampNumber(value, 1, 20, 3)),
+  clipboardMaxLength: value => Math.round(clampNumber(value, 1, 200, 40)),
+  clipboardMinLetterRatio: value => clampNumber(value, 0, 1, 0.8),
+  prefixTransform: value => (MdictParser.KEY_TRANSFORMS[value] ? value : 'identity'),
+  mdxFile: value => (typeof value === 'string' ? value.trim() : undefined),
+  mddFile: value => (typeof value === 'string' ? value.trim() : undefined),
+  cssFile: value => (typeof value === 'string' ? value.trim() : undefined),
+  dictionariesRoot: value => (typeof value === 'string' ? value.trim() : ''),
+  activeProfile: value => (typeof value === 'string' && value.trim() ? value.trim() : Profiles.DEFAULT_PROFILE)
+};
+
+const BOOLEAN_FIELDS = ['clipboardMonitor', 'showSourceBadge', 'debugResources', 'autoReloadOnChange', 'showRedirectBanner', 'buildIndexOnLoad', 'autoSpeak', 'offlineMode', 'caseInsensitiveLookup', 'ignoreAccents', 'briefFirstSentence', 'onlineCacheCompress'];
+
+// 按 FIELD_RULES / BOOLEAN_FIELDS 校验单个配置项，返回修正后的值；没有规则的配置项原样返回
+function normalizeField(field, value) {
+  if (FIELD_RULES[field]) {
+    return FIELD_RULES[field](value);
+  }
+  if (BOOLEAN_FIELDS.includes(field)) {
+    return typeof value === 'boolean' ? value : value === 'true' || value === 1;
+  }
+  return value;
+}
+
+// 校验并修正所有已保存的配置项，返回修正记录 [{ field, from, to }]
+function normalizeConfig(store) {
+  const corrections = [];
+
+  const fix = (field, from, to) => {
+    if (JSON.stringify(from) === JSON.stringify(to)) {
+      return;
+    }
+    if (to === undefined) {
+      store.delete(field);
+    } else {
+      store.set(field, to);
+    }
+    corrections.push({ field, from, to });
+  };
+
+  Object.keys(FIELD_RULES).forEach(field => {
+    if (store.has(field)) {
+      const value = store.get(field);
+      fix(field, value, FIELD_RULES[field](value));
+    }
+  });
+
+  BOOLEAN_FIELDS.forEach(field => {
+    if (store.has(field) && typeof store.get(field) !== 'boolean') {
+      const value = store.get(field);
+      fix(field, value, normalizeField(field, value));
+    }
+  });
+
+  ['dictionaryOverrides', 'windowBounds', 'profiles'].forEach(field => {
+    if (store.has(field)) {
+      const value = store.get(field);
+      if (!value || typeof value !== 'object' || Array.isArray(value)) {
+        fix(field, value, {});
+      }
+    }
+  });
+
+  // 词典列表：去掉没有 mdxFile 的项，路径字段只保留字符串
+  if (store.has('dictionaries')) {
+    const dictionaries = store.get('dictionaries');
+    const valid = (Array.isArray(dictionaries) ? dictionaries : [])
+      .filter(item => item && typeof item === 'object' && typeof item.mdxFile === 'string' && item.mdxFile.trim())
+      .map(item => {
+        const entry = { mdxFile: item.mdxFile.trim() };
+        ['mddFile', 'cssFile'].forEach(field => {
+          if (typeof item[field] === 'string' && item[field].trim()) {
+            entry[field] = item[field].trim();
+          }
+        });
+        return entry;
+      });
+    fix('dictionaries', dictionaries, valid);
+  }
+
+  return corrections;
+}
+
+// 配置结构的版本号；结构变化时加一，并在 MIGRATIONS 中添加对应的升级步骤
+const CONFIG_SCHEMA_VERSION = 2;
+
+// MIGRATIONS[n] 把版本 n 的配置升级到 n + 1，新字段使用默认值（不写入即可）
+const MIGRATIONS = [
+  // 0 -> 1：没有版本号的旧配置，字段结构相同，只需记录版本号
+  () => {},
+  // 1 -> 2：加入配置方案，现有的词典和显示设置成为默认方案
+  store => {
+    if (!store.has('profiles')) {
+      store.set('profiles', { [Profiles.DEFAULT_PROFILE]: Profiles.snapshot(store) });
+      store.set('activeProfile', Profiles.DEFAULT_PROFILE);
+    }
+  }
+];
+
+// 升级旧版本的配置，升级前备份原文件；返回 { from, to, backup }，无需升级时返回 null
+function migrateConfig(store) {
+  const version = store.get('schemaVersion', 0);
+  if (version >= CONFIG_SCHEMA_VERSION) {
+    if (version > CONFIG_SCHEMA_VERSION) {
+      console.warn(`Config schema ${version} is newer than this version of QuickDict (${CONFIG_SCHEMA_VERSION})`);
+    }
+    return null;
+  }
+
+  let backup = null;
+  if (fs.existsSync(store.path)) {
+    backup = `${store.path}.v${version}.bak`;
+    fs.copyFileSync(store.path, backup);
+  }
+
+  for (let v = version; v < CONFIG_SCHEMA_VERSION; v++) {
+    MIGRATIONS[v](store);
+  }
+  store.set('schemaVersion', CONFIG_SCHEMA_VERSION);
+
+  return { from: version, to: CONFIG_SCHEMA_VERSION, backup };
+}
+
+module.exports = {
+  CONFIG_SCHEMA_VERSION,
+  canonicalizeHotkey,
+  migrateConfig,
+  hotkeyToAccelerator,
+  normalizeConfig,
+  normalizeField
+};
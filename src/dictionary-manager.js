@@ -0,0 +1,430 @@
+const path = require('path');
+const MdictParser = require('./mdict-parser');
+const PinyinIndex = require('./pinyin-index');
+
+// 管理多部词典：按配置顺序查询，结果按词典分节显示
+class DictionaryManager {
+  constructor(configs, displaySettings = {}) {
+    this.configs = configs;
+    this.displaySettings = displaySettings;
+    this.dictionaries = [];
+    this.lastEntry = null;
+  }
+
+  clearCaches() {
+    this.dictionaries.forEach(dictionary => dictionary.clearCaches());
+  }
+
+  // 第一部成功加载的词典，单词典功能（元数据、规范化预览等）以它为准
+  get primary() {
+    return this.dictionaries[0] || null;
+  }
+
+  // 逐个加载词典，单个失败只跳过该词典；全部失败时抛出第一个错误
+  async load() {
+    let firstError = null;
+
+    for (const config of this.configs) {
+      const dictionary = new MdictParser(config.mdx, config.mdd, config.css, this.displaySettings);
+      try {
+        await dictionary.load();
+        this.dictionaries.push(dictionary);
+      } catch (error) {
+        console.error('Skipping dictionary:', config.mdx, error.message);
+        firstError = firstError || error;
+      }
+    }
+
+    if (this.dictionaries.length === 0) {
+      throw firstError || new Error('No dictionaries configured');
+    }
+    return true;
+  }
+
+  // 切换主词典：把第一部词典移到最后，下一部成为主词典并排在查询结果的最前面；
+  // 轮到最后一部之后再切换回到第一部。返回新的主词典
+  cycleActive() {
+    if (this.dictionaries.length > 1) {
+      this.dictionaries.push(this.dictionaries.shift());
+    }
+    return this.primary;
+  }
+
+  // 词典的配置键（MDX 文件名），用于保存每部词典的覆盖设置
+  static getKey(dictionary) {
+    return path.basename(dictionary.mdxFile);
+  }
+
+  updateDisplaySettings(settings) {
+    this.displaySettings = settings;
+    this.dictionaries.forEach(dictionary => dictionary.updateDisplaySettings(settings));
+  }
+
+  updateSearchOptions(options) {
+    this.dictionaries.forEach(dictionary => dictionary.updateSearchOptions(options));
+  }
+
+  updateOverrides(name, overrides) {
+    this.dictionaries
+      .filter(dictionary => DictionaryManager.getKey(dictionary) === name)
+      .forEach(dictionary => dictionary.updateOverrides(overrides));
+  }
+
+  getDictionaryName() {
+    return this.dictionaries.map(dictionary => dictionary.getDictionaryName()).join(', ');
+  }
+
+  getMetadata() {
+    return this.primary.getMetadata();
+  }
+
+  // 每部已加载词典的信息
+  getInfo() {
+    return this.dictionaries.map(dictionary => dictionary.getInfo());
+  }
+
+  getStatus() {
+    return {
+      ...this.primary.getStatus(),
+      dictionaries: this.dictionaries.map(dictionary => dictionary.getStatus())
+    };
+  }
+
+  isKeyCaseSensitive() {
+    return this.primary.isKeyCaseSensitive();
+  }
+
+  normalizeQuery(word) {
+    return this.primary.normalizeQuery(word);
+  }
+
+  async lookup(word) {
+    return (await this.find(word)).html;
+  }
+
+  // 查询所有词典，返回渲染结果和有结果的词典名称；
+  // guard 由调用方按用户操作创建，各词典共用（保护内部按词典分别计数）
+  async find(word, guard = MdictParser.createLookupGuard()) {
+    const sections = [];
+    let notFoundHtml = null;
+
+    for (const dictionary of this.dictionaries) {
+      const section = await dictionary.lookupSection(word, guard);
+      if (section.entry) {
+        sections.push({ dictionary, entry: section.entry });
+      } else if (notFoundHtml === null) {
+        notFoundHtml = section.html;
+      }
+    }
+
+    if (sections.length === 0) {
+      return { html: notFoundHtml, sources: [] };
+    }
+
+    this.lastEntry = { word, sections };
+    return {
+      html: this.renderSections(this.lastEntry),
+      sources: sections.map(section => section.dictionary.getDictionaryName())
+    };
+  }
+
+  // 查询并只返回各词典的完整正文（不含页面样式），没有找到时返回 null；不改变当前词条
+  async lookupBody(word) {
+    const savedEntry = this.lastEntry;
+    const { sources } = await this.find(word);
+    const body = sources.length > 0
+      ? this.lastEntry.sections.map(section => section.dictionary.renderEntryBody(section.entry, true)).join('')
+      : null;
+    this.lastEntry = savedEntry;
+    return body;
+  }
+
+  // 在所有词典中随机取一个词条：按各词典的词条数加权选择词典，使每个词条的概率相同
+  randomEntry(random = Math.random) {
+    const counts = this.dictionaries.map(dictionary => dictionary.getKeywordList().length);
+    const total = counts.reduce((sum, count) => sum + count, 0);
+    if (total === 0) {
+      return null;
+    }
+
+    let pick = Math.floor(random() * total);
+    const index = counts.findIndex(count => (pick -= count) < 0);
+    const entry = this.dictionaries[index].randomEntry(random);
+    return entry ? { ...entry, source: this.dictionaries[index].getDictionaryName() } : null;
+  }
+
+  // 只有一部词典有结果时与单词典显示一致，否则每部词典一节，以词典标题为节标题；
+  // full 为 true 时不截断超长词条
+  renderSections(lastEntry, full = false) {
+    const entries = lastEntry.sections.map(section => ({
+      dictionary: section.dictionary,
+      entry: { ...section.entry, pos: lastEntry.pos || null }
+    }));
+
+    if (entries.length === 1) {
+      return entries[0].dictionary.renderEntry(entries[0].entry, full);
+    }
+
+    // 每部词典的样式表限定在各自的节内
+    const scopeOf = dictionary => `dictionary-${this.dictionaries.indexOf(dictionary)}`;
+    const body = entries.map(({ dictionary, entry }) => `
+      <div class="dictionary-section ${scopeOf(dictionary)}">
+        <div class="dictionary-header">${dictionary.escapeHtml(dictionary.getDictionaryName())}</div>
+        ${dictionary.renderEntryBody(entry, full)}
+      </div>
+    `).join('');
+    const cssContent = entries
+      .map(({ dictionary }) => MdictParser.scopeCss(dictionary.cssContent, `.${scopeOf(dictionary)}`))
+      .join('\n');
+
+    return this.primary.wrapPage(body, cssContent);
+  }
+
+  // 用第一部词典的页面样式包装其他来源的内容（如在线查询结果）
+  wrapPage(body, cssContent = '') {
+    return this.primary.wrapPage(body, cssContent);
+  }
+
+  restyleCurrent() {
+    if (!this.lastEntry) {
+      return null;
+    }
+    return this.renderSections(this.lastEntry);
+  }
+
+  renderCurrentFull() {
+    if (!this.lastEntry) {
+      return null;
+    }
+    return this.renderSections(this.lastEntry, true);
+  }
+
+  // 当前词条的发音音频，按词典顺序取第一个有音频的词典
+  getPronunciationUrl() {
+    if (!this.lastEntry) {
+      return null;
+    }
+    for (const section of this.lastEntry.sections) {
+      const url = section.dictionary.getPronunciationUrl(section.entry);
+      if (url) {
+        return url;
+      }
+    }
+    return null;
+  }
+
+  // 按词典顺序查找资源，返回第一个找到的
+  async getResource(resourceName) {
+    for (const dictionary of this.dictionaries) {
+      const resource = dictionary.locateResource(resourceName);
+      if (resource) {
+        return resource;
+      }
+    }
+    return null;
+  }
+
+  buildPrefixIndex(transformName) {
+    return this.dictionaries.reduce((total, dictionary) => total + dictionary.buildPrefixIndex(transformName), 0);
+  }
+
+  // 预先为所有词典建立前缀索引，返回每部词典的索引项数和估算内存
+  buildIndexes() {
+    return this.dictionaries.map(dictionary => {
+      const entries = dictionary.buildPrefixIndex();
+      return { name: dictionary.getDictionaryName(), entries, bytes: dictionary.getPrefixIndexBytes() };
+    });
+  }
+
+  // 合并各词典的前缀匹配结果并标注来源词典；大小写不同的同一词头只保留先出现的词典，
+  // 合并后按与单词典相同的规则排序
+  prefixSearch(prefix, limit = 20) {
+    const seen = new Set();
+    const results = [];
+    this.dictionaries.forEach(dictionary => {
+      dictionary.prefixSearch(prefix, limit).forEach(word => {
+        if (!seen.has(word.toLowerCase())) {
+          seen.add(word.toLowerCase());
+          results.push({ word, source: dictionary.getDictionaryName() });
+        }
+      });
+    });
+
+    const compare = MdictParser.comparePrefixMatches(prefix);
+    return results.sort((a, b) => compare(a.word, b.word)).slice(0, limit);
+  }
+
+  // 分页列出以 prefix 开头的词头，合并各词典（大小写不同的词头只保留一个）。
+  // 每部词典取前 offset + limit + 1 个，合并排序后再取这一页，翻页时顺序不变
+  prefixPage(prefix, offset = 0, limit = 50) {
+    const seen = new Set();
+    const merged = [];
+    let totalEstimate = 0;
+    this.dictionaries.forEach(dictionary => {
+      const page = dictionary.prefixPage(prefix, 0, offset + limit + 1);
+      totalEstimate += page.totalEstimate;
+      page.words.forEach(word => {
+        if (!seen.has(word.toLowerCase())) {
+          seen.add(word.toLowerCase());
+          merged.push({ word, source: dictionary.getDictionaryName() });
+        }
+      });
+    });
+
+    merged.sort((a, b) => {
+      const foldedA = a.word.toLowerCase();
+      const foldedB = b.word.toLowerCase();
+      return foldedA < foldedB ? -1 : foldedA > foldedB ? 1 : 0;
+    });
+    return {
+      results: merged.slice(offset, offset + limit),
+      hasMore: merged.length > offset + limit,
+      totalEstimate
+    };
+  }
+
+  // 合并各词典的通配符 / 正则搜索结果，按字母排序
+  patternSearch(pattern, limit = 50) {
+    const seen = new Set();
+    const results = [];
+    this.dictionaries.forEach(dictionary => {
+      dictionary.patternSearch(pattern, limit).forEach(word => {
+        if (!seen.has(word)) {
+          seen.add(word);
+          results.push({ word, source: dictionary.getDictionaryName() });
+        }
+      });
+    });
+    return results.sort((a, b) => a.word.localeCompare(b.word)).slice(0, limit);
+  }
+
+  // 为搜索结果补充简短释义（取自来源词典），在线结果和已带有 brief（拼音读音）的结果不处理
+  addBriefs(results) {
+    return results.map(result => {
+      const dictionary = this.dictionaries.find(item => item.getDictionaryName() === result.source);
+      return dictionary && result.brief === undefined ? { ...result, brief: dictionary.getBrief(result.word) } : result;
+    });
+  }
+
+  // 在建立了拼音索引的词典中按拼音搜索汉字词头，读音放在 brief 中
+  pinyinSearch(query, limit = 20) {
+    if (!PinyinIndex.isPinyinQuery(query)) {
+      return [];
+    }
+    const results = [];
+    this.dictionaries.filter(dictionary => dictionary.pinyinIndex).forEach(dictionary => {
+      dictionary.pinyinIndex.search(query, limit).forEach(match => {
+        results.push({ word: match.word, source: dictionary.getDictionaryName(), brief: match.reading });
+      });
+    });
+    return results.slice(0, limit);
+  }
+
+  // 依次在各词典的释义中全文搜索，合计最多 limit 个结果；
+  // onProgress(scanned, total) 报告所有词典合计的扫描进度
+  async fulltextSearch(query, limit = 50, onProgress = null) {
+    const total = this.dictionaries.reduce((sum, dictionary) => sum + dictionary.getKeywordList().length, 0);
+    const results = [];
+    let offset = 0;
+
+    for (const dictionary of this.dictionaries) {
+      if (results.length >= limit) {
+        break;
+      }
+      const source = dictionary.getDictionaryName();
+      const matches = await dictionary.fulltextSearch(query, limit - results.length,
+        onProgress ? scanned => onProgress(offset + scanned, total) : null);
+      matches.forEach(match => results.push({ ...match, source }));
+      offset += dictionary.getKeywordList().length;
+    }
+    return results;
+  }
+
+  // 搜索词头：含通配符时按模式匹配；前缀匹配太少时，用模糊匹配补充（带 distance，用于提示“是否要找”）
+  searchWords(query, limit = 20) {
+    if (MdictParser.isPattern(query)) {
+      return this.patternSearch(query, limit);
+    }
+
+    const results = this.prefixSearch(query, limit);
+    const seen = new Set(results.map(result => result.word.toLowerCase()));
+    this.pinyinSearch(query, limit).forEach(result => {
+      if (!seen.has(result.word.toLowerCase())) {
+        seen.add(result.word.toLowerCase());
+        results.push(result);
+      }
+    });
+    if (results.length >= DictionaryManager.FUZZY_FALLBACK_THRESHOLD) {
+      return DictionaryManager.rankSuggestions(query, results).slice(0, limit);
+    }
+
+    const fuzzy = [];
+    this.dictionaries.forEach(dictionary => {
+      dictionary.fuzzySearch(query).forEach(match => {
+        if (!seen.has(match.keyText.toLowerCase())) {
+          seen.add(match.keyText.toLowerCase());
+          fuzzy.push({ word: match.keyText, source: dictionary.getDictionaryName(), distance: match.distance });
+        }
+      });
+    });
+
+    return DictionaryManager.rankSuggestions(query, results.concat(fuzzy)).slice(0, limit);
+  }
+
+  // 按来源分组搜索结果：本地词典按加载顺序在前，在线结果在后；组内保持原有排序。
+  // 返回 [{ label, online, results }]，没有结果的来源不出现
+  groupResults(results) {
+    const order = this.dictionaries.map(dictionary => dictionary.getDictionaryName());
+    const groups = new Map();
+    results.forEach(result => {
+      if (!groups.has(result.source)) {
+        groups.set(result.source, { label: result.source, online: result.online === true, results: [] });
+      }
+      groups.get(result.source).results.push(result);
+    });
+
+    const rank = group => {
+      if (group.online) {
+        return order.length + 1;
+      }
+      const index = order.indexOf(group.label);
+      return index === -1 ? order.length : index;
+    };
+    return [...groups.values()].sort((a, b) => rank(a) - rank(b));
+  }
+}
+
+// 前缀匹配少于此数量时启用模糊搜索
+DictionaryManager.FUZZY_FALLBACK_THRESHOLD = 5;
+
+// 搜索建议的分级：与输入完全一致 0，忽略大小写一致 1，前缀匹配 2，模糊匹配（带 distance）3
+DictionaryManager.scoreSuggestion = function(query, result) {
+  const typed = query.trim();
+  if (result.word === typed) {
+    return 0;
+  }
+  if (result.word.toLowerCase() === typed.toLowerCase()) {
+    return 1;
+  }
+  return result.distance === undefined ? 2 : 3;
+};
+
+// 按分级排序搜索建议；同级的前缀匹配按长度、模糊匹配按距离和长度，再以本地词典优先于在线结果。
+// 排序是稳定的，其余情况保持原有顺序
+DictionaryManager.rankSuggestions = function(query, results) {
+  const keyOf = result => [
+    DictionaryManager.scoreSuggestion(query, result),
+    result.distance || 0,
+    result.word.length,
+    result.online ? 1 : 0
+  ];
+  return results
+    .map(result => ({ result, key: keyOf(result) }))
+    .sort((a, b) => {
+      const i = a.key.findIndex((value, index) => value !== b.key[index]);
+      return i === -1 ? 0 : a.key[i] - b.key[i];
+    })
+    .map(item => item.result);
+};
+
+module.exports = DictionaryManager;
@@ -0,0 +1,155 @@
+const fs = require('fs');
+const path = require('path');
+const zlib = require('zlib');
+const { pipeline } = require('stream/promises');
+
+const EOCD_SIGNATURE = 0x06054b50;
+const CENTRAL_SIGNATURE = 0x02014b50;
+const LOCAL_SIGNATURE = 0x04034b50;
+const DICTIONARY_EXTENSIONS = ['.mdx', '.mdd', '.css'];
+
+// 读取 ZIP 中央目录，返回 [{ name, method, compressedSize, size, localOffset, encrypted }]；不支持 ZIP64
+async function readZipEntries(zipPath) {
+  const name = path.basename(zipPath);
+  const handle = await fs.promises.open(zipPath, 'r');
+  try {
+    const { size } = await handle.stat();
+
+    // 中央目录结尾记录在文件末尾，后面最多跟 65535 字节的注释
+    const tailSize = Math.min(size, 22 + 0xFFFF);
+    const tail = Buffer.alloc(tailSize);
+    await handle.read(tail, 0, tailSize, size - tailSize);
+    let eocd = -1;
+    for (let i = tailSize - 22; i >= 0; i--) {
+      if (tail.readUInt32LE(i) === EOCD_SIGNATURE) {
+        eocd = i;
+        break;
+      }
+    }
+    if (eocd === -1) {
+      throw new Error(`"${name}" is not a zip archive`);
+    }
+
+    const count = tail.readUInt16LE(eocd + 10);
+    const directorySize = tail.readUInt32LE(eocd + 12);
+    const directoryOffset = tail.readUInt32LE(eocd + 16);
+    if (count === 0xFFFF || directoryOffset === 0xFFFFFFFF) {
+      throw new Error(`"${name}" is a ZIP64 archive, which is not supported`);
+    }
+
+    const directory = Buffer.alloc(directorySize);
+    await handle.read(directory, 0, directorySize, directoryOffset);
+
+    const entries = [];
+    let offset = 0;
+    for (let i = 0; i < count; i++) {
+      if (offset + 46 > directory.length || directory.readUInt32LE(offset) !== CENTRAL_SIGNATURE) {
+        throw new Error(`"${name}" has a corrupt zip directory`);
+      }
+      const flags = directory.readUInt16LE(offset + 8);
+      const nameLength = directory.readUInt16LE(offset + 28);
+      const extraLength = directory.readUInt16LE(offset + 30);
+      const commentLength = directory.readUInt16LE(offset + 32);
+      entries.push({
+        // 第 11 位表示文件名为 UTF-8，否则按单字节编码读取（只用于判断扩展名和生成文件名）
+        name: directory.subarray(offset + 46, offset + 46 + nameLength).toString(flags & 0x800 ? 'utf-8' : 'latin1'),
+        method: directory.readUInt16LE(offset + 10),
+        compressedSize: directory.readUInt32LE(offset + 20),
+        size: directory.readUInt32LE(offset + 24),
+        localOffset: directory.readUInt32LE(offset + 42),
+        encrypted: (flags & 1) !== 0
+      });
+      offset += 46 + nameLength + extraLength + commentLength;
+    }
+    return entries;
+  } finally {
+    await handle.close();
+  }
+}
+
+// 解压单个条目到 target（先写临时文件，完成后再改名），支持存储和 deflate 两种方式
+async function extractEntry(zipPath, entry, target) {
+  if (entry.encrypted) {
+    throw new Error(`"${entry.name}" in "${path.basename(zipPath)}" is password-protected`);
+  }
+  if (entry.method !== 0 && entry.method !== 8) {
+    throw new Error(`"${entry.name}" uses an unsupported zip compression method (${entry.method})`);
+  }
+
+  const handle = await fs.promises.open(zipPath, 'r');
+  const header = Buffer.alloc(30);
+  try {
+    await handle.read(header, 0, 30, entry.localOffset);
+  } finally {
+    await handle.close();
+  }
+  if (header.readUInt32LE(0) !== LOCAL_SIGNATURE) {
+    throw new Error(`"${entry.name}" has a corrupt zip header`);
+  }
+  const dataStart = entry.localOffset + 30 + header.readUInt16LE(26) + header.readUInt16LE(28);
+
+  await fs.promises.mkdir(path.dirname(target), { recursive: true });
+  const temporary = `${target}.partial`;
+  if (entry.compressedSize === 0) {
+    await fs.promises.writeFile(temporary, Buffer.alloc(0));
+  } else {
+    const streams = [fs.createReadStream(zipPath, { start: dataStart, end: dataStart + entry.compressedSize - 1 })];
+    if (entry.method === 8) {
+      streams.push(zlib.createInflateRaw());
+    }
+    await pipeline(...streams, fs.createWriteStream(temporary));
+  }
+  await fs.promises.rename(temporary, target);
+}
+
+// 已解压的文件大小一致且不早于压缩包时，无需重新解压
+async function isUpToDate(target, size, packageTime) {
+  try {
+    const stat = await fs.promises.stat(target);
+    return stat.size === size && stat.mtimeMs >= packageTime;
+  } catch (error) {
+    return false;
+  }
+}
+
+// 解压打包成 zip 的词典：取第一个 .mdx 为主文件，优先匹配同名的 .mdd 和 .css，
+// 没有同名文件时取压缩包中的第一个。文件解压到 targetRoot 下以压缩包命名的目录，
+// 返回 { mdx, mdd, css }，压缩包中没有的文件为 null
+async function unpackDictionaryPackage(zipPath, targetRoot) {
+  const extensionOf = entry => path.extname(entry.name).toLowerCase();
+  const stemOf = entry => path.basename(entry.name, path.extname(entry.name)).toLowerCase();
+
+  const entries = (await readZipEntries(zipPath))
+    .filter(entry => !entry.name.endsWith('/') && DICTIONARY_EXTENSIONS.includes(extensionOf(entry)));
+  const mdxEntry = entries.find(entry => extensionOf(entry) === '.mdx');
+  if (!mdxEntry) {
+    throw new Error(`"${path.basename(zipPath)}" does not contain an MDX file`);
+  }
+  const siblingOf = extension => {
+    const candidates = entries.filter(entry => extensionOf(entry) === extension);
+    return candidates.find(entry => stemOf(entry) === stemOf(mdxEntry)) || candidates[0] || null;
+  };
+
+  const targetDir = path.join(targetRoot, path.basename(zipPath, path.extname(zipPath)));
+  const packageTime = (await fs.promises.stat(zipPath)).mtimeMs;
+  const files = {};
+  for (const [field, entry] of [['mdx', mdxEntry], ['mdd', siblingOf('.mdd')], ['css', siblingOf('.css')]]) {
+    if (!entry) {
+      files[field] = null;
+      continue;
+    }
+    // 只取文件名，忽略压缩包内的目录，避免写到目标目录之外
+    const target = path.join(targetDir, path.basename(entry.name.replace(/\\/g, '/')));
+    if (!await isUpToDate(target, entry.size, packageTime)) {
+      console.log(`Extracting ${entry.name} from ${path.basename(zipPath)}`);
+      await extractEntry(zipPath, entry, target);
+    }
+    files[field] = target;
+  }
+  return files;
+}
+
+module.exports = {
+  readZipEntries,
+  unpackDictionaryPackage
+};
@@ -0,0 +1,29 @@
+// 发送给界面的错误代码，界面根据代码显示对应的提示
+const ErrorCodes = {
+  NO_DICTIONARY: 'NoDictionary',
+  PARSE_ERROR: 'ParseError',
+  NETWORK: 'Network',
+  OFFLINE: 'Offline',
+  HOTKEY_INVALID: 'HotkeyInvalid',
+  HOTKEY_UNAVAILABLE: 'HotkeyUnavailable',
+  INVALID_PROFILE: 'InvalidProfile',
+  INVALID_ARGUMENT: 'InvalidArgument',
+  FILE_ERROR: 'FileError'
+};
+
+class CommandError extends Error {
+  constructor(code, message) {
+    super(message);
+    this.code = code;
+  }
+
+  // IPC 只能传递普通对象
+  toJSON() {
+    return { code: this.code, message: this.message };
+  }
+}
+
+module.exports = {
+  ErrorCodes,
+  CommandError
+};
@@ -0,0 +1,184 @@
+const Store = require('electron-store');
+
+// 收藏的单词：保存在数据目录的 favorites.json 中，最新收藏的在前
+class Favorites {
+  constructor() {
+    this.store = new Store({ name: 'favorites' });
+  }
+
+  list() {
+    const entries = this.store.get('entries', []);
+    return Array.isArray(entries) ? entries : [];
+  }
+
+  // 按词典的大小写规则查找收藏项的位置
+  indexOf(word, caseSensitive = false) {
+    const key = caseSensitive ? word : word.toLowerCase();
+    return this.list().findIndex(entry => (caseSensitive ? entry.word : entry.word.toLowerCase()) === key);
+  }
+
+  has(word, caseSensitive = false) {
+    return this.indexOf(word, caseSensitive) !== -1;
+  }
+
+  // 添加收藏，已收藏时只更新备注；返回是否新增
+  add(word, dictionary, note = '', caseSensitive = false) {
+    const entries = this.list();
+    const index = this.indexOf(word, caseSensitive);
+    if (index !== -1) {
+      if (note) {
+        entries[index].note = note;
+        this.store.set('entries', entries);
+      }
+      return false;
+    }
+
+    entries.unshift({ word, dictionary, note, addedAt: Date.now() });
+    this.store.set('entries', entries);
+    return true;
+  }
+
+  remove(word, caseSensitive = false) {
+    const entries = this.list();
+    const index = this.indexOf(word, caseSensitive);
+    if (index === -1) {
+      return false;
+    }
+    entries.splice(index, 1);
+    this.store.set('entries', entries);
+    return true;
+  }
+
+  // 合并导入的收藏，已收藏的词跳过；保持文件中的顺序（文件第一项在最前），返回新增数
+  importEntries(imported, source = '', caseSensitive = false) {
+    let added = 0;
+    [...imported].reverse().forEach(entry => {
+      if (this.add(entry.word, source, entry.note || '', caseSensitive)) {
+        added++;
+      }
+    });
+    return added;
+  }
+}
+
+// 导入导出格式：txt 每行一个词（GoldenDict 的单词列表），
+// csv 为欧路词典生词本的“单词,释义”两列，xml 为欧路词典生词本的 <wordbook>
+Favorites.FORMATS = ['txt', 'csv', 'xml'];
+
+// 单个词的最大长度，超过时视为格式错误
+const MAX_WORD_LENGTH = 200;
+
+function isValidWord(word) {
+  return word.length > 0 && word.length <= MAX_WORD_LENGTH && !/[\x00-\x1f<>]/.test(word);
+}
+
+// 解析一行 CSV，引号不配对时返回 null
+function parseCsvLine(line) {
+  const fields = [];
+  let field = '';
+  let quoted = false;
+  for (let i = 0; i < line.length; i++) {
+    const char = line[i];
+    if (quoted) {
+      if (char === '"' && line[i + 1] === '"') {
+        field += '"';
+        i++;
+      } else if (char === '"') {
+        quoted = false;
+      } else {
+        field += char;
+      }
+    } else if (char === '"' && field === '') {
+      quoted = true;
+    } else if (char === ',') {
+      fields.push(field);
+      field = '';
+    } else {
+      field += char;
+    }
+  }
+  if (quoted) {
+    return null;
+  }
+  fields.push(field);
+  return fields;
+}
+
+function csvField(value) {
+  return /[",\r\n]/.test(value) ? `"${value.replace(/"/g, '""')}"` : value;
+}
+
+function escapeXml(text) {
+  return String(text).replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;').replace(/"/g, '&quot;');
+}
+
+function unescapeXml(text) {
+  return text
+    .replace(/<!\[CDATA\[([\s\S]*?)\]\]>/g, '$1')
+    .replace(/&lt;/g, '<').replace(/&gt;/g, '>').replace(/&quot;/g, '"').replace(/&apos;/g, "'")
+    .replace(/&amp;/g, '&');
+}
+
+// 解析导入文件的内容，返回 { entries: [{ word, note }], skipped }，skipped 为格式错误而跳过的行数
+Favorites.parse = function(text, format) {
+  const entries = [];
+  let skipped = 0;
+  const accept = (word, note = '') => {
+    word = word.trim();
+    if (isValidWord(word)) {
+      entries.push({ word, note: note.trim() });
+    } else {
+      skipped++;
+    }
+  };
+
+  text = text.replace(/^\uFEFF/, '');
+  if (format === 'xml') {
+    const items = text.match(/<item>[\s\S]*?<\/item>/g) || [];
+    items.forEach(item => {
+      const word = item.match(/<word>([\s\S]*?)<\/word>/);
+      const note = item.match(/<trans>([\s\S]*?)<\/trans>/);
+      accept(word ? unescapeXml(word[1]) : '', note ? unescapeXml(note[1]) : '');
+    });
+    return { entries, skipped };
+  }
+
+  text.split(/\r?\n/).forEach((line, index) => {
+    if (!line.trim()) {
+      return;
+    }
+    if (format !== 'csv') {
+      accept(line);
+      return;
+    }
+    const fields = parseCsvLine(line);
+    if (!fields) {
+      skipped++;
+      return;
+    }
+    // 第一行可能是表头
+    if (index === 0 && /^(word|单词)$/i.test(fields[0].trim())) {
+      return;
+    }
+    accept(fields[0], fields[1] || '');
+  });
+  return { entries, skipped };
+};
+
+// 把收藏列表转换为导出文件的内容
+Favorites.format = function(entries, format) {
+  if (format === 'xml') {
+    const items = entries.map(entry => `  <item>
+    <word>${escapeXml(entry.word)}</word>
+    <trans>${escapeXml(entry.note || '')}</trans>
+  </item>`);
+    return `<?xml version="1.0" encoding="UTF-8"?>\n<wordbook>\n${items.join('\n')}${items.length ? '\n' : ''}</wordbook>\n`;
+  }
+  if (format === 'csv') {
+    // 带 BOM，Excel 和欧路词典才会按 UTF-8 打开
+    return `\uFEFFword,note\r\n${entries.map(entry => `${csvField(entry.word)},${csvField(entry.note || '')}\r\n`).join('')}`;
+  }
+  return entries.map(entry => `${entry.word}\n`).join('');
+};
+
+module.exports = Favorites;
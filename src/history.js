@@ -0,0 +1,39 @@
+const Store = require('electron-store');
+
+// 查询历史：保存在数据目录的 history.json 中，最新的在前
+class History {
+  constructor(maxEntries = 100) {
+    this.store = new Store({ name: 'history' });
+    this.maxEntries = maxEntries;
+  }
+
+  list() {
+    const entries = this.store.get('entries', []);
+    return Array.isArray(entries) ? entries : [];
+  }
+
+  // 记录一次查询；与上一条是同一个词时只更新时间和词典
+  add(word, dictionary) {
+    const entries = this.list();
+    const entry = { word, timestamp: Date.now(), dictionary };
+
+    if (entries.length > 0 && entries[0].word.toLowerCase() === word.toLowerCase()) {
+      entries[0] = entry;
+    } else {
+      entries.unshift(entry);
+    }
+
+    this.store.set('entries', entries.slice(0, this.maxEntries));
+  }
+
+  setMaxEntries(maxEntries) {
+    this.maxEntries = maxEntries;
+    this.store.set('entries', this.list().slice(0, maxEntries));
+  }
+
+  clear() {
+    this.store.set('entries', []);
+  }
+}
+
+module.exports = History;
@@ -1,98 +1,428 @@
-const { app, BrowserWindow, globalShortcut, clipboard, ipcMain, protocol, net } = require('electron');
+const { app, BrowserWindow, globalShortcut, clipboard, ipcMain, protocol, net, dialog, screen } = require('electron');
 const path = require('path');
+const fs = require('fs');
 const MdictParser = require('./mdict-parser');
+const DictionaryManager = require('./dictionary-manager');
+const { unpackDictionaryPackage } = require('./dictionary-package');
+const { exportWordlist, FORMATS: WORDLIST_FORMATS } = require('./wordlist-export');
+const PinyinIndex = require('./pinyin-index');
+const History = require('./history');
+const Favorites = require('./favorites');
+const Profiles = require('./profiles');
+const ByteLruCache = require('./byte-cache');
+const { classifyClipboardText, DEFAULT_OPTIONS: CLIPBOARD_FILTER_DEFAULTS } = require('./clipboard-filter');
+const { createProvider, escapeHtml, probeProviders, setRequestTimeout, setRequestConcurrency } = require('./online-providers');
+const OnlineCache = require('./online-cache');
+const SourceToggle = require('./source-toggle');
+const { ErrorCodes, CommandError } = require('./errors');
+const { normalizeConfig, normalizeField, migrateConfig, canonicalizeHotkey, hotkeyToAccelerator } = require('./config');
 const Store = require('electron-store');
 
 // 持久化存储配置
 const store = new Store();
 const DEFAULT_HOTKEY = 'Alt+M';
 
+// 启动时升级旧版本的配置（会先备份），再校验并修正无效或不规范的值
+const migration = migrateConfig(store);
+if (migration) {
+  console.log(`Config migrated from schema ${migration.from} to ${migration.to}`, migration.backup ? `(backup: ${migration.backup})` : '');
+}
+
+const startupCorrections = normalizeConfig(store);
+if (startupCorrections.length > 0) {
+  console.log('Config corrected:', startupCorrections);
+}
+
 let mainWindow = null;
 let lookupWindow = null;
 let settingsWindow = null;
 let dictionary = null;
+let dictionaryError = null;
 let currentHotkey = store.get('hotkey', DEFAULT_HOTKEY);
 let clipboardMonitorEnabled = store.get('clipboardMonitor', true);
+let clipboardMaxWords = store.get('clipboardMaxWords', CLIPBOARD_FILTER_DEFAULTS.maxWords);
+let clipboardMaxLength = store.get('clipboardMaxLength', CLIPBOARD_FILTER_DEFAULTS.maxLength);
+let clipboardMinLetterRatio = store.get('clipboardMinLetterRatio', CLIPBOARD_FILTER_DEFAULTS.minLetterRatio);
+let hotkeyTarget = store.get('hotkeyTarget', 'lookup');
+let cycleDictionaryHotkey = store.get('cycleDictionaryHotkey', '');
+let autoReloadOnChange = store.get('autoReloadOnChange', false);
+let autoSpeak = store.get('autoSpeak', false);
+const history = new History(store.get('historySize', 100));
+const favorites = new Favorites();
+const profiles = new Profiles(store);
+let onlineLanguage = store.get('onlineLanguage', 'en');
+let onlineProvider = createProvider(store.get('onlineProvider', 'freeDictionary'), { language: onlineLanguage });
+const onlineCache = new OnlineCache(store.get('onlineCacheTtlHours', 168), store.get('onlineCacheCompress', false));
+let onlineTimeoutSeconds = store.get('onlineTimeoutSeconds', 10);
+setRequestTimeout(onlineTimeoutSeconds);
+let onlineMaxConcurrent = store.get('onlineMaxConcurrent', 2);
+setRequestConcurrency(onlineMaxConcurrent);
+
+// 显示设置（切换配置方案时重新读取）
+function readDisplaySettings() {
+  return {
+    fontFamily: store.get('fontFamily', 'Segoe UI'),
+    fontSize: store.get('fontSize', '14'),
+    lineHeight: store.get('lineHeight', '1.6'),
+    fontFallbacks: store.get('fontFallbacks', MdictParser.DEFAULT_FONT_FALLBACKS),
+    showSourceBadge: store.get('showSourceBadge', false),
+    maxRenderBytes: store.get('maxRenderBytes', 512 * 1024),
+    debugResources: store.get('debugResources', false),
+    preferredRegion: store.get('preferredRegion', 'auto'),
+    renderCacheBytes: store.get('renderCacheBytes', 8 * 1024 * 1024),
+    showRedirectBanner: store.get('showRedirectBanner', true),
+    redirectBannerText: store.get('redirectBannerText', '(redirected from "{word}")'),
+    theme: store.get('theme', 'light'),
+    customTheme: store.get('customTheme', {})
+  };
+}
 
-// 显示设置
-let displaySettings = {
-  fontFamily: store.get('fontFamily', 'Segoe UI'),
-  fontSize: store.get('fontSize', '14'),
-  lineHeight: store.get('lineHeight', '1.6')
+let displaySettings = readDisplaySettings();
+
+// 查询设置
+let searchOptions = {
+  fuzzyDistanceDivisor: store.get('fuzzyDistanceDivisor', 4),
+  fuzzyDistanceCap: store.get('fuzzyDistanceCap', 3),
+  prefixTransform: store.get('prefixTransform', 'identity'),
+  caseInsensitiveLookup: store.get('caseInsensitiveLookup', true),
+  ignoreAccents: store.get('ignoreAccents', false),
+  briefLength: store.get('briefLength', 80),
+  briefFirstSentence: store.get('briefFirstSentence', true),
+  queryTrimChars: store.get('queryTrimChars', MdictParser.DEFAULT_QUERY_TRIM_CHARS)
 };
 
-// 配置词典路径
+// 配置词典路径（默认值，可在配置中用 mdxFile / mddFile / cssFile 覆盖）
 const DICTIONARY_PATH = 'D:\\Documents\\词典\\牛津高阶英汉双解词典(第9版)_v20191111';
 const MDX_FILE = path.join(DICTIONARY_PATH, '牛津高阶英汉双解词典(第9版).mdx');
 const MDD_FILE = path.join(DICTIONARY_PATH, '牛津高阶英汉双解词典(第9版).mdd');
 const CSS_FILE = path.join(DICTIONARY_PATH, 'oalecd9.css');
 
-// 资源缓存
-const resourceCache = new Map();
-
-// 转换快捷键格式为 Electron accelerator 格式
-function parseHotkey(hotkey) {
-  const parts = hotkey.split('+').map(p => p.trim().toLowerCase());
-  const acceleratorParts = [];
-
-  parts.forEach(part => {
-    switch(part) {
-      case 'ctrl':
-      case 'control':
-        acceleratorParts.push('CommandOrControl');
-        break;
-      case 'cmd':
-      case 'command':
-        acceleratorParts.push('CommandOrControl');
-        break;
-      case 'alt':
-        acceleratorParts.push('Alt');
-        break;
-      case 'shift':
-        acceleratorParts.push('Shift');
-        break;
-      default:
-        // 主键
-        acceleratorParts.push(part.toUpperCase());
+// 在目录下按文件名查找（用于词典文件夹被移动后重新定位）
+function findFileByName(root, fileName, maxDepth = 3) {
+  let entries;
+  try {
+    entries = fs.readdirSync(root, { withFileTypes: true });
+  } catch (error) {
+    return null;
+  }
+
+  for (const entry of entries) {
+    if (entry.isFile() && entry.name === fileName) {
+      return path.join(root, entry.name);
     }
-  });
+  }
+
+  if (maxDepth > 0) {
+    for (const entry of entries) {
+      if (entry.isDirectory()) {
+        const found = findFileByName(path.join(root, entry.name), fileName, maxDepth - 1);
+        if (found) {
+          return found;
+        }
+      }
+    }
+  }
 
-  return acceleratorParts.join('+');
+  return null;
 }
 
-// 注册全局快捷键
-function registerGlobalHotkey(hotkey) {
-  const accelerator = parseHotkey(hotkey);
+// 解析配置中的词典路径：相对路径基于数据目录，解析符号链接，
+// 文件不存在时在 dictionariesRoot 下按文件名查找并更新配置
+function resolveDictionaryPath(key, defaultPath) {
+  return resolveStoredPath(store.get(key, defaultPath), relinked => store.set(key, relinked));
+}
 
-  // 注销旧的快捷键
-  globalShortcut.unregisterAll();
+function resolveStoredPath(storedPath, onRelink) {
+  if (!storedPath) {
+    return storedPath;
+  }
+
+  const absolutePath = path.isAbsolute(storedPath)
+    ? storedPath
+    : path.resolve(app.getPath('userData'), storedPath);
+
+  if (fs.existsSync(absolutePath)) {
+    try {
+      return fs.realpathSync(absolutePath);
+    } catch (error) {
+      return absolutePath;
+    }
+  }
+
+  const dictionariesRoot = store.get('dictionariesRoot', '');
+  if (dictionariesRoot) {
+    const relinked = findFileByName(dictionariesRoot, path.basename(storedPath));
+    if (relinked) {
+      console.log(`Relinked: ${storedPath} -> ${relinked}`);
+      onRelink(relinked);
+      return relinked;
+    }
+  }
+
+  return absolutePath;
+}
+
+function resolveDictionaryFiles() {
+  return {
+    mdx: resolveDictionaryPath('mdxFile', MDX_FILE),
+    mdd: resolveDictionaryPath('mddFile', MDD_FILE),
+    css: resolveDictionaryPath('cssFile', CSS_FILE)
+  };
+}
+
+let dictionaryFiles = resolveDictionaryFiles();
+
+// 词典列表：配置中的 dictionaries 按顺序查询，未配置时只使用上面这一部词典
+function resolveDictionaryConfigs() {
+  const configs = store.get('dictionaries', []);
+  if (!Array.isArray(configs) || configs.length === 0) {
+    return [dictionaryFiles];
+  }
+
+  const relink = (index, field) => relinked => {
+    const list = store.get('dictionaries', []);
+    list[index][field] = relinked;
+    store.set('dictionaries', list);
+  };
+
+  // 没有单独指定样式表的词典使用全局的 cssFile（如果配置了）
+  const globalCss = store.has('cssFile') ? dictionaryFiles.css : null;
+  return configs.map((config, index) => ({
+    mdx: resolveStoredPath(config.mdxFile, relink(index, 'mdxFile')),
+    mdd: resolveStoredPath(config.mddFile, relink(index, 'mddFile')),
+    css: config.cssFile ? resolveStoredPath(config.cssFile, relink(index, 'cssFile')) : globalCss
+  }));
+}
+
+let dictionaryConfigs = resolveDictionaryConfigs();
+
+// 离线模式：不发起任何在线请求。没有设置过时，配置了本地词典就默认离线
+let offlineMode = store.get('offlineMode', dictionaryConfigs.some(config => config.mdx && fs.existsSync(config.mdx)));
+
+// 第一部词典的覆盖设置（按 MDX 文件名保存），设置界面编辑的是这部词典
+function getDictionaryKey() {
+  return path.basename(dictionaryConfigs[0].mdx);
+}
+
+function getDictionaryOverrides(name = getDictionaryKey()) {
+  const allOverrides = store.get('dictionaryOverrides', {});
+  return allOverrides[name] || {};
+}
+
+// 清除词典的所有覆盖项，恢复默认行为
+function resetDictionaryOverrides(name = getDictionaryKey()) {
+  const allOverrides = store.get('dictionaryOverrides', {});
+  delete allOverrides[name];
+  store.set('dictionaryOverrides', allOverrides);
+
+  if (dictionary) {
+    dictionary.updateOverrides(name, {});
+    updatePinyinIndexes();
+  }
+}
+
+function setDictionaryOverride(name, value) {
+  const allOverrides = store.get('dictionaryOverrides', {});
+  const overrides = allOverrides[getDictionaryKey()] || {};
+  overrides[name] = value;
+  allOverrides[getDictionaryKey()] = overrides;
+  store.set('dictionaryOverrides', allOverrides);
+
+  if (dictionary) {
+    dictionary.updateOverrides(getDictionaryKey(), overrides);
+    updatePinyinIndexes();
+  }
+}
+
+// 资源缓存：按总字节数限制容量，超出时淘汰最久未使用的资源
+const resourceCache = new ByteLruCache(store.get('resourceCacheBytes', 32 * 1024 * 1024));
+
+function clearResourceCache() {
+  resourceCache.clear();
+}
+
+// 读取 MDD 资源（优先使用缓存），找不到时返回 null
+async function loadResource(name) {
+  const cached = resourceCache.get(name);
+  if (cached) {
+    return cached;
+  }
 
-  // 注册新的快捷键
-  const success = globalShortcut.register(accelerator, () => {
+  const manager = dictionary;
+  if (!manager) {
+    return null;
+  }
+
+  const resource = await manager.getResource(name);
+  if (!resource) {
+    return null;
+  }
+
+  const data = Buffer.from(resource);
+  resourceCache.set(name, data);
+  return data;
+}
+
+// 根据扩展名确定资源的 MIME 类型
+const MIME_TYPES = {
+  '.png': 'image/png',
+  '.jpg': 'image/jpeg',
+  '.jpeg': 'image/jpeg',
+  '.gif': 'image/gif',
+  '.svg': 'image/svg+xml',
+  '.bmp': 'image/bmp',
+  '.webp': 'image/webp',
+  '.mp3': 'audio/mpeg',
+  '.wav': 'audio/wav',
+  '.ogg': 'audio/ogg',
+  '.spx': 'audio/ogg',
+  '.css': 'text/css',
+  '.js': 'application/javascript'
+};
+
+function getMimeType(resourceName) {
+  return MIME_TYPES[path.extname(resourceName).toLowerCase()] || 'application/octet-stream';
+}
+
+// 根据配置选择快捷键的目标窗口：查询弹窗或主窗口
+function getHotkeyHandler(target) {
+  if (target === 'main') {
+    return () => {
+      if (!mainWindow) {
+        createMainWindow();
+      }
+      if (mainWindow.isMinimized()) {
+        mainWindow.restore();
+      }
+      mainWindow.show();
+      mainWindow.focus();
+    };
+  }
+
+  return () => {
     const selectedText = clipboard.readText();
     if (selectedText && selectedText.trim()) {
       createLookupWindow();
       lookupWord(selectedText);
     }
-  });
+  };
+}
+
+// 注册全局快捷键，返回 { success, error }：
+// error 为 HotkeyInvalid（无法解析）或 HotkeyUnavailable（已被其他程序占用）
+function registerGlobalHotkey(hotkey) {
+  const accelerator = hotkeyToAccelerator(hotkey);
+  if (!accelerator) {
+    console.error(`Invalid hotkey: ${hotkey}`);
+    return { success: false, error: ErrorCodes.HOTKEY_INVALID };
+  }
+
+  // 注销旧的快捷键（包括切换词典的快捷键，随后重新注册）
+  globalShortcut.unregisterAll();
+  registerCycleDictionaryHotkey();
+
+  // 注册新的快捷键（注册时确定目标窗口）
+  let success = false;
+  try {
+    success = globalShortcut.register(accelerator, getHotkeyHandler(hotkeyTarget));
+  } catch (error) {
+    console.error(`Invalid hotkey: ${hotkey} (${accelerator})`, error);
+    if (hotkey !== currentHotkey) {
+      registerGlobalHotkey(currentHotkey);
+    }
+    return { success: false, error: ErrorCodes.HOTKEY_INVALID };
+  }
 
   if (success) {
     console.log(`Global hotkey registered: ${hotkey} (${accelerator})`);
     currentHotkey = hotkey;
     store.set('hotkey', hotkey);
-  } else {
-    console.error(`Failed to register hotkey: ${hotkey} (${accelerator})`);
+    return { success: true, error: null };
+  }
+
+  console.error(`Failed to register hotkey: ${hotkey} (${accelerator})`);
+  // 恢复原来的快捷键
+  if (hotkey !== currentHotkey) {
+    registerGlobalHotkey(currentHotkey);
   }
+  return { success: false, error: ErrorCodes.HOTKEY_UNAVAILABLE };
+}
+
+// 切换主词典的快捷键（可选，默认不设置），返回是否注册成功
+function registerCycleDictionaryHotkey() {
+  const accelerator = hotkeyToAccelerator(cycleDictionaryHotkey);
+  if (!accelerator) {
+    return false;
+  }
+  try {
+    return globalShortcut.register(accelerator, cycleDictionary);
+  } catch (error) {
+    console.error(`Invalid hotkey: ${cycleDictionaryHotkey} (${accelerator})`, error);
+    return false;
+  }
+}
+
+// 依次把下一部词典设为主词典，通知所有窗口（查询窗口显示提示），并在新的主词典中重新显示当前词
+function cycleDictionary(manager = dictionary) {
+  if (!manager || manager.dictionaries.length < 2) {
+    return null;
+  }
+
+  const name = manager.cycleActive().getDictionaryName();
+  broadcast('dictionary-cycled', { name });
+  if (manager.lastEntry && lookupWindow) {
+    lookupWord(manager.lastEntry.word, { navigating: true });
+  }
+  return name;
+}
+
+// 恢复窗口上次的位置和大小（按窗口名保存在 windowBounds 中），
+// 保存的位置不在任何显示器上时移到最近的显示器内
+function getWindowBounds(name, defaults) {
+  const saved = store.get('windowBounds', {})[name];
+  if (!saved || typeof saved.x !== 'number' || typeof saved.y !== 'number') {
+    return defaults;
+  }
+
+  const bounds = {
+    x: saved.x,
+    y: saved.y,
+    width: defaults.resizable === false ? defaults.width : saved.width || defaults.width,
+    height: defaults.resizable === false ? defaults.height : saved.height || defaults.height
+  };
+  const area = screen.getDisplayMatching(bounds).workArea;
+  bounds.width = Math.min(bounds.width, area.width);
+  bounds.height = Math.min(bounds.height, area.height);
+  bounds.x = Math.min(Math.max(bounds.x, area.x), area.x + area.width - bounds.width);
+  bounds.y = Math.min(Math.max(bounds.y, area.y), area.y + area.height - bounds.height);
+
+  return { ...defaults, ...bounds };
+}
+
+// 窗口移动或调整大小后（停止 500ms）保存位置和大小
+function trackWindowBounds(win, name) {
+  let timer = null;
+  const save = () => {
+    clearTimeout(timer);
+    timer = setTimeout(() => {
+      if (win.isDestroyed() || win.isMinimized() || win.isMaximized()) {
+        return;
+      }
+      const allBounds = store.get('windowBounds', {});
+      allBounds[name] = win.getBounds();
+      store.set('windowBounds', allBounds);
+    }, 500);
+  };
 
-  return success;
+  win.on('move', save);
+  win.on('resize', save);
+  win.on('closed', () => clearTimeout(timer));
 }
 
 // 创建主窗口（配置界面）
 function createMainWindow() {
   mainWindow = new BrowserWindow({
-    width: 550,
-    height: 650,
-    resizable: false,
+    ...getWindowBounds('main', { width: 550, height: 650, resizable: false }),
     webPreferences: {
       nodeIntegration: true,
       contextIsolation: false
@@ -101,6 +431,7 @@ function createMainWindow() {
   });
 
   mainWindow.loadFile('src/index.html');
+  trackWindowBounds(mainWindow, 'main');
 
   mainWindow.on('closed', () => {
     mainWindow = null;
@@ -115,10 +446,8 @@ function createLookupWindow() {
   }
 
   lookupWindow = new BrowserWindow({
-    width: 600,
-    height: 700,
+    ...getWindowBounds('lookup', { width: 600, height: 700, resizable: true }),
     frame: true,
-    resizable: true,
     alwaysOnTop: true,
     skipTaskbar: false,
     autoHideMenuBar: true,
@@ -131,6 +460,7 @@ function createLookupWindow() {
   });
 
   lookupWindow.loadFile('src/lookup.html');
+  trackWindowBounds(lookupWindow, 'lookup');
 
   // 失去焦点时自动关闭（可选）
   lookupWindow.on('blur', () => {
@@ -139,6 +469,8 @@ function createLookupWindow() {
 
   lookupWindow.on('closed', () => {
     lookupWindow = null;
+    navigation.words = [];
+    navigation.index = -1;
   });
 
   return lookupWindow;
@@ -173,253 +505,1544 @@ function createSettingsWindow() {
   return settingsWindow;
 }
 
-// 查询单词
-async function lookupWord(word) {
-  if (!word || !word.trim()) return;
+// 词典路径指向 .zip 时，解压到数据目录的 dictionaries 下并改用解压出的文件；
+// 压缩包没有变化时不会重复解压
+async function unpackDictionaryConfigs() {
+  for (const config of dictionaryConfigs) {
+    const packagePath = config.package || config.mdx;
+    if (packagePath && path.extname(packagePath).toLowerCase() === '.zip' && fs.existsSync(packagePath)) {
+      const files = await unpackDictionaryPackage(packagePath, path.join(app.getPath('userData'), 'dictionaries'));
+      Object.assign(config, { package: packagePath, mdx: files.mdx, mdd: files.mdd, css: files.css || config.css });
+    }
+  }
+}
 
+// 为覆盖设置中启用 pinyinIndex 的词典建立拼音索引（在后台进行，建好之前拼音搜索没有结果）。
+// 索引保存在数据目录的 indexes 下，以 MDX 文件的大小和修改时间为签名，词典变化后重建
+function updatePinyinIndexes() {
   if (!dictionary) {
-    console.log('Loading dictionary...');
+    return;
+  }
+  const manager = dictionary;
+  manager.dictionaries.forEach(async loaded => {
+    const name = DictionaryManager.getKey(loaded);
+    if (!getDictionaryOverrides(name).pinyinIndex) {
+      loaded.pinyinIndex = null;
+      return;
+    }
+    if (loaded.pinyinIndex) {
+      return;
+    }
+
     try {
-      dictionary = new MdictParser(MDX_FILE, MDD_FILE, CSS_FILE, displaySettings);
-      await dictionary.load();
-      console.log('Dictionary loaded successfully');
+      const file = path.join(app.getPath('userData'), 'indexes', `${name}.pinyin.json`);
+      const signature = PinyinIndex.signatureOf(loaded.mdxFile);
+      let index = await PinyinIndex.load(file, signature);
+      if (!index) {
+        console.log(`Building pinyin index for ${name}...`);
+        index = await PinyinIndex.build(loaded.getKeywordList().map(item => item.keyText));
+        await index.save(file, signature);
+      }
+      // 建立期间词典被重新加载或关闭了拼音索引时丢弃结果
+      if (dictionary === manager && getDictionaryOverrides(name).pinyinIndex) {
+        loaded.pinyinIndex = index;
+        console.log(`Pinyin index ready for ${name}: ${index.size} entries`);
+      }
     } catch (error) {
-      console.error('Failed to load dictionary:', error);
-      return;
+      console.error(`Failed to build pinyin index for ${name}:`, error.message);
     }
-  } else {
-    // 更新显示设置
-    dictionary.updateDisplaySettings(displaySettings);
-  }
+  });
+}
 
-  const result = await dictionary.lookup(word.trim());
+// 正在进行的词典加载（Promise），没有加载时为 null
+let dictionaryLoading = null;
 
-  // 发送结果到查询窗口
-  if (lookupWindow && lookupWindow.webContents) {
-    lookupWindow.webContents.send('lookup-result', {
-      word: word,
-      result: result
+function broadcast(channel, data) {
+  BrowserWindow.getAllWindows().forEach(win => {
+    win.webContents.send(channel, data);
+  });
+}
+
+// 开始加载词典并返回加载的 Promise，加载期间再次调用时返回同一个 Promise，不会重复解析。
+// js-mdict 同步解析文件，解析期间主进程无法处理其他消息
+function startDictionaryLoad() {
+  if (!dictionaryLoading) {
+    dictionaryLoading = loadDictionary().finally(() => {
+      dictionaryLoading = null;
     });
   }
+  return dictionaryLoading;
 }
 
-// 监听剪贴板变化
-let lastClipboardText = '';
-
-function checkClipboard() {
-  const text = clipboard.readText();
-  if (text && text !== lastClipboardText) {
-    lastClipboardText = text;
-
-    // 如果是英文单词，则查询
-    if (/^[a-zA-Z\s\-']+$/.test(text.trim()) && text.trim().length > 0) {
-      createLookupWindow();
-      lookupWord(text);
+async function loadDictionary() {
+  console.log('Loading dictionary...');
+  try {
+    await unpackDictionaryConfigs();
+    const manager = new DictionaryManager(dictionaryConfigs, displaySettings);
+    await manager.load();
+    dictionary = manager;
+    dictionary.dictionaries.forEach(loaded => {
+      const name = DictionaryManager.getKey(loaded);
+      dictionary.updateOverrides(name, getDictionaryOverrides(name));
+    });
+    dictionary.updateSearchOptions(searchOptions);
+    console.log(`${dictionary.dictionaries.length} dictionaries loaded successfully`);
+    updatePinyinIndexes();
+
+    // 加载时预建索引（大词典会占用较多内存，默认在首次搜索时才建立）
+    if (store.get('buildIndexOnLoad', false)) {
+      dictionary.buildIndexes().forEach(index => {
+        console.log(`Index built for ${index.name}: ${index.entries} entries, ~${(index.bytes / 1024 / 1024).toFixed(1)} MB`);
+      });
     }
+    return manager;
+  } catch (error) {
+    console.error('Failed to load dictionary:', error);
+    dictionary = null;
+    // 区分没有词典文件和词典文件无法解析
+    const missing = dictionaryConfigs.every(config => !config.mdx || !fs.existsSync(config.mdx));
+    dictionaryError = missing
+      ? new CommandError(ErrorCodes.NO_DICTIONARY, 'No dictionary file found')
+      : new CommandError(ErrorCodes.PARSE_ERROR, error.message);
+    return null;
   }
 }
 
-// 监听剪贴板
-let clipboardInterval = null;
+// 确保词典已加载（需要时等待加载完成），加载失败时返回 null
+async function ensureDictionary() {
+  if (!dictionary) {
+    return startDictionaryLoad();
+  }
 
-function startClipboardMonitor() {
-  if (clipboardInterval) return;
+  // 更新显示设置
+  dictionary.updateDisplaySettings(displaySettings);
+  return dictionary;
+}
 
-  clipboardInterval = setInterval(checkClipboard, 500);
+// 查询窗口的后退 / 前进记录（与浏览器相同），查询窗口关闭时清空
+const navigation = { words: [], index: -1 };
+const MAX_NAVIGATION_ENTRIES = 100;
+
+// 记录一次新的查询：丢弃当前位置之后的前进记录，与当前词相同时不重复记录
+function pushNavigation(word) {
+  if (navigation.words[navigation.index] === word) {
+    return;
+  }
+  navigation.words = navigation.words.slice(0, navigation.index + 1);
+  navigation.words.push(word);
+  if (navigation.words.length > MAX_NAVIGATION_ENTRIES) {
+    navigation.words.shift();
+  }
+  navigation.index = navigation.words.length - 1;
 }
 
-function stopClipboardMonitor() {
-  if (clipboardInterval) {
-    clearInterval(clipboardInterval);
-    clipboardInterval = null;
+function sendNavigationState() {
+  if (lookupWindow && lookupWindow.webContents) {
+    lookupWindow.webContents.send('navigation-state', {
+      canGoBack: navigation.index > 0,
+      canGoForward: navigation.index < navigation.words.length - 1
+    });
   }
 }
 
-// IPC 通信处理
-ipcMain.on('lookup-word', (event, word) => {
-  createLookupWindow();
-  lookupWord(word);
-});
+// 后退（step = -1）或前进（step = 1）并重新显示该词
+function navigate(step) {
+  const index = navigation.index + step;
+  if (index < 0 || index >= navigation.words.length) {
+    return;
+  }
+  navigation.index = index;
+  lookupWord(navigation.words[index], { navigating: true });
+}
 
-ipcMain.on('open-settings', () => {
-  createSettingsWindow();
-});
+// 查询单词；navigating 为 true 表示由后退 / 前进触发，不记录历史
+async function lookupWord(word, options = {}) {
+  if (!word || !word.trim()) return;
 
-// 同步获取显示设置
-ipcMain.on('get-display-settings', (event) => {
-  event.returnValue = {
-    ...displaySettings,
-    clipboardMonitor: clipboardMonitorEnabled
-  };
-});
+  // 查询期间词典可能被重新加载（dictionary 被置为 null），之后一直使用这次加载好的词典
+  const manager = await ensureDictionary();
+  if (!manager) {
+    if (lookupWindow && lookupWindow.webContents) {
+      lookupWindow.webContents.send('lookup-result', { word: word, error: dictionaryError.toJSON() });
+    }
+    return;
+  }
 
-// 设置字体
-ipcMain.on('set-font-family', (event, fontFamily) => {
-  displaySettings.fontFamily = fontFamily;
-  store.set('fontFamily', fontFamily);
+  // 每次用户操作（输入、取词、点击 entry:// 链接）创建一个查询保护，
+  // 本次查询在各词典中的 @@@LINK 重定向共用它
+  const guard = MdictParser.createLookupGuard();
+  const { html: result, sources } = await manager.find(word.trim(), guard);
 
-  // 更新解析器设置
-  if (dictionary) {
-    dictionary.updateDisplaySettings(displaySettings);
+  shownLookup = { word: word.trim(), source: 'local' };
+  if (sources.length > 0) {
+    sourceToggle.remember(word.trim(), 'local', result);
   }
 
-  // 通知所有查询窗口更新字体
-  BrowserWindow.getAllWindows().forEach(win => {
-    if (win !== mainWindow && win !== settingsWindow) {
-      win.webContents.send('update-display-settings', displaySettings);
-    }
-  });
-});
-
-// 设置字号
-ipcMain.on('set-font-size', (event, fontSize) => {
-  displaySettings.fontSize = fontSize;
-  store.set('fontSize', fontSize);
+  if (!options.navigating) {
+    pushNavigation(word.trim());
 
-  if (dictionary) {
-    dictionary.updateDisplaySettings(displaySettings);
-  }
+    // 找到释义时记录到查询历史
+    if (sources.length > 0) {
+      history.add(word.trim(), sources.join(', '));
+    }
 
-  BrowserWindow.getAllWindows().forEach(win => {
-    if (win !== mainWindow && win !== settingsWindow) {
-      win.webContents.send('update-display-settings', displaySettings);
+    if (sources.length > 0 && autoSpeak) {
+      speakWord(word.trim());
     }
-  });
-});
+  }
 
-// 设置行高
-ipcMain.on('set-line-height', (event, lineHeight) => {
-  displaySettings.lineHeight = lineHeight;
-  store.set('lineHeight', lineHeight);
+  // 发送结果到查询窗口；找到释义时附带收藏状态，用于显示星标
+  if (lookupWindow && lookupWindow.webContents) {
+    lookupWindow.webContents.send('lookup-result', {
+      word: word,
+      result: result,
+      favorited: sources.length > 0 ? favorites.has(word.trim(), manager.isKeyCaseSensitive()) : null
+    });
+  }
+  sendNavigationState();
+}
 
-  if (dictionary) {
-    dictionary.updateDisplaySettings(displaySettings);
+// 朗读单词：词典中有对应的发音音频时播放音频，否则由查询窗口使用系统语音合成
+function speakWord(word) {
+  if (!lookupWindow || !lookupWindow.webContents) {
+    return;
   }
 
-  BrowserWindow.getAllWindows().forEach(win => {
-    if (win !== mainWindow && win !== settingsWindow) {
-      win.webContents.send('update-display-settings', displaySettings);
-    }
+  const isCurrent = dictionary && dictionary.lastEntry && dictionary.lastEntry.word.toLowerCase() === word.toLowerCase();
+  const region = displaySettings.preferredRegion;
+  const audio = isCurrent ? dictionary.getPronunciationUrl() : null;
+  lookupWindow.webContents.send('speak-word', {
+    word: word,
+    // 离线模式下不播放在线音频，改用语音合成
+    audio: offlineMode && audio && /^https?:/i.test(audio) ? null : audio,
+    lang: region === 'us' ? 'en-US' : region === 'uk' ? 'en-GB' : 'en'
   });
-});
+}
 
-ipcMain.on('set-hotkey', (event, hotkey) => {
-  const success = registerGlobalHotkey(hotkey);
+// 读取在线词条：优先使用未过期的缓存，离线模式或网络失败时退回过期的缓存。
+// 返回 { entry, stale }，没有结果时 entry 为 null；requireEntries 为 true 时忽略没有结构化数据的旧缓存
+async function fetchOnlineEntry(word, requireEntries = false) {
+  let cached = onlineCache.get(onlineProvider.cacheName, word);
+  if (cached && requireEntries && !cached.entries) {
+    cached = null;
+  }
+  if (cached && !onlineCache.isExpired(cached)) {
+    return { entry: cached, stale: false };
+  }
+  if (offlineMode) {
+    // 离线模式下只使用缓存（即使已过期）
+    if (!cached) {
+      throw new CommandError(ErrorCodes.OFFLINE, 'Online lookup is disabled in offline mode');
+    }
+    return { entry: cached, stale: true };
+  }
 
-  if (success) {
-    if (mainWindow) {
-      mainWindow.webContents.send('hotkey-updated', hotkey);
+  try {
+    const entry = await onlineProvider.lookup(word.trim());
+    if (entry) {
+      onlineCache.set(onlineProvider.cacheName, word, entry);
     }
-    if (settingsWindow) {
-      settingsWindow.webContents.send('hotkey-updated', hotkey);
+    return { entry, stale: false };
+  } catch (error) {
+    // 网络失败时使用过期的缓存
+    if (!cached) {
+      throw error;
     }
+    console.warn('Online lookup failed, using cached result:', error.message);
+    return { entry: cached, stale: true };
   }
-});
+}
 
-ipcMain.on('toggle-clipboard-monitor', (event, enabled) => {
-  clipboardMonitorEnabled = enabled;
-  store.set('clipboardMonitor', enabled);
+function renderOnlineEntry(entry, stale) {
+  const staleNote = stale ? '<div class="redirect-info">(offline - showing a cached result)</div>' : '';
+  const body = `<div class="source-badge">${escapeHtml(onlineProvider.label)}</div>${staleNote}${entry.html}`;
+  return dictionary ? dictionary.wrapPage(body) : body;
+}
 
-  if (enabled) {
-    startClipboardMonitor();
-  } else {
-    stopClipboardMonitor();
+// 查询窗口当前显示的词和来源（local / online），用于在两种来源之间切换
+let shownLookup = { word: null, source: 'local' };
+
+const sourceToggle = new SourceToggle({
+  local: async (word) => {
+    const manager = await ensureDictionary();
+    if (!manager) {
+      return null;
+    }
+    const { html, sources } = await manager.find(word, MdictParser.createLookupGuard());
+    return sources.length > 0 ? html : null;
+  },
+  online: async (word) => {
+    const { entry, stale } = await fetchOnlineEntry(word);
+    return entry ? renderOnlineEntry(entry, stale) : null;
   }
 });
 
-// 获取MDD资源
-ipcMain.handle('get-mdd-resource', async (event, resourceName) => {
-  if (!dictionary) {
+// 在本地释义和在线释义之间切换查询窗口当前的词；另一种来源没有结果时留在当前来源并提示
+async function toggleSource() {
+  if (!shownLookup.word || !lookupWindow || !lookupWindow.webContents) {
     return null;
   }
 
-  // 检查缓存
-  const cacheKey = `mdd:${resourceName}`;
-  if (resourceCache.has(cacheKey)) {
-    return resourceCache.get(cacheKey);
+  const { word, source } = shownLookup;
+  const toggled = await sourceToggle.toggle(word, source);
+  shownLookup = { word, source: toggled.source };
+  if (lookupWindow && lookupWindow.webContents) {
+    if (toggled.missing) {
+      lookupWindow.webContents.send('show-notice', `No ${toggled.missing} definition for "${word}"`);
+    } else {
+      lookupWindow.webContents.send('lookup-result', { word, result: toggled.html });
+    }
   }
+  return toggled.source;
+}
 
+// 在线查询：通过配置中选择的在线词典提供者
+async function lookupWordOnline(word) {
+  if (!word || !word.trim()) return;
+
+  let result;
   try {
-    const resource = await dictionary.getResource(resourceName);
+    const { entry, stale } = await fetchOnlineEntry(word);
 
-    // 缓存资源
-    if (resource) {
-      resourceCache.set(cacheKey, resource);
+    if (entry) {
+      history.add(entry.word, onlineProvider.label);
+      result = renderOnlineEntry(entry, stale);
+      shownLookup = { word: word.trim(), source: 'online' };
+      sourceToggle.remember(word.trim(), 'online', result);
+    } else {
+      result = `<div class="not-found">
+        <h3>Not Found</h3>
+        <p>Word "<strong>${escapeHtml(word)}</strong>" not found in ${escapeHtml(onlineProvider.label)}.</p>
+      </div>`;
     }
+  } catch (error) {
+    console.error('Online lookup error:', error);
+    if (lookupWindow && lookupWindow.webContents) {
+      lookupWindow.webContents.send('lookup-result', {
+        word: word,
+        error: (error instanceof CommandError ? error : new CommandError(ErrorCodes.NETWORK, error.message)).toJSON()
+      });
+    }
+    return;
+  }
+
+  if (lookupWindow && lookupWindow.webContents) {
+    lookupWindow.webContents.send('lookup-result', {
+      word: word,
+      result: result
+    });
+  }
+}
+
+// 重新读取配置中的词典路径（配置被修改或切换方案后）
+function refreshDictionaryConfigs() {
+  dictionaryFiles = resolveDictionaryFiles();
+  dictionaryConfigs = resolveDictionaryConfigs();
+  if (autoReloadOnChange) {
+    startDictionaryWatcher();
+  }
+}
+
+// 重新加载词典：丢弃已解析的数据和资源缓存，并重新渲染当前词条。
+// 重新加载期间再次调用时等待同一次加载完成，不会并行加载两次
+let reloadInFlight = null;
+
+function reloadDictionary() {
+  if (!reloadInFlight) {
+    reloadInFlight = doReloadDictionary().finally(() => {
+      reloadInFlight = null;
+    });
+  }
+  return reloadInFlight;
+}
+
+async function doReloadDictionary() {
+  const currentWord = dictionary && dictionary.lastEntry ? dictionary.lastEntry.word : null;
+
+  dictionary = null;
+  clearResourceCache();
+  sourceToggle.clear();
+
+  const reloaded = await ensureDictionary();
+
+  broadcast('dictionary-reloaded', {
+    success: !!reloaded,
+    name: reloaded ? reloaded.getDictionaryName() : path.basename(dictionaryConfigs[0].mdx)
+  });
+
+  if (reloaded && currentWord && lookupWindow) {
+    lookupWord(currentWord);
+  }
+
+  return reloaded;
+}
+
+// 监听词典文件变化，文件稳定后自动重新加载
+let dictionaryWatchers = [];
+let reloadTimer = null;
+
+// 所有词典的 MDX / MDD（含分卷）/ CSS 文件；打包成 zip 的词典只监听压缩包
+function getDictionaryFileList() {
+  return dictionaryConfigs.flatMap(config => (config.package
+    ? [config.package]
+    : [config.mdx, ...MdictParser.findMddFiles(config.mdx, config.mdd), config.css]));
+}
 
-    return resource;
+function scheduleDictionaryReload() {
+  clearTimeout(reloadTimer);
+  reloadTimer = setTimeout(async () => {
+    // 等待文件写入完成：两次检查之间大小不变才重新加载
+    const sizes = () => getDictionaryFileList().map(file => {
+      try {
+        return fs.statSync(file).size;
+      } catch (error) {
+        return -1;
+      }
+    }).join(',');
+
+    const before = sizes();
+    await new Promise(resolve => setTimeout(resolve, 500));
+    if (sizes() !== before) {
+      scheduleDictionaryReload();
+      return;
+    }
+
+    console.log('Dictionary files changed, reloading...');
+    reloadDictionary();
+  }, 1000);
+}
+
+function startDictionaryWatcher() {
+  stopDictionaryWatcher();
+
+  getDictionaryFileList().forEach(file => {
+    if (file && fs.existsSync(file)) {
+      try {
+        dictionaryWatchers.push(fs.watch(file, scheduleDictionaryReload));
+      } catch (error) {
+        console.error('Failed to watch file:', file, error);
+      }
+    }
+  });
+}
+
+function stopDictionaryWatcher() {
+  clearTimeout(reloadTimer);
+  dictionaryWatchers.forEach(watcher => watcher.close());
+  dictionaryWatchers = [];
+}
+
+// 监听剪贴板变化
+let lastClipboardText = '';
+let lastClipboardQuery = null;
+
+function checkClipboard() {
+  if (!clipboardMonitorEnabled) return;
+
+  const copied = clipboard.readText().trim();
+  if (!copied || copied === lastClipboardText) {
+    return;
+  }
+  // 不论是否查询都记下，同样的内容不再重复判断
+  lastClipboardText = copied;
+
+  // 复制的文字常带有首尾标点或引号（"word," 等），先去掉再判断是否为单词
+  const text = MdictParser.trimQuery(copied, searchOptions.queryTrimChars);
+
+  // 只查询较短的单词或短语，复制代码、网址、整段文字等时忽略
+  const { accept } = classifyClipboardText(text, {
+    maxWords: clipboardMaxWords,
+    maxLength: clipboardMaxLength,
+    minLetterRatio: clipboardMinLetterRatio
+  }, lastClipboardQuery);
+  if (!accept) {
+    return;
+  }
+
+  lastClipboardQuery = text;
+  createLookupWindow();
+  lookupWord(text);
+}
+
+// 监听剪贴板
+let clipboardInterval = null;
+
+function startClipboardMonitor() {
+  if (clipboardInterval) return;
+
+  // 以开启时的剪贴板内容为起点，只对之后的变化做查询
+  lastClipboardText = clipboard.readText().trim();
+  clipboardInterval = setInterval(checkClipboard, 500);
+}
+
+function stopClipboardMonitor() {
+  if (clipboardInterval) {
+    clearInterval(clipboardInterval);
+    clipboardInterval = null;
+  }
+}
+
+// IPC 通信处理
+ipcMain.on('lookup-word', (event, word) => {
+  createLookupWindow();
+  lookupWord(word);
+});
+
+ipcMain.on('nav-back', () => {
+  navigate(-1);
+});
+
+ipcMain.on('nav-forward', () => {
+  navigate(1);
+});
+
+ipcMain.on('lookup-word-online', (event, word) => {
+  createLookupWindow();
+  lookupWordOnline(word);
+});
+
+// 当前词在本地释义和在线释义之间切换，返回切换后的来源
+handleCommand('toggle-source', () => toggleSource());
+
+ipcMain.on('toggle-source', () => {
+  toggleSource();
+});
+
+// 返回解析后的在线词条 { word, source, stale, entries }，由界面自行渲染；没有结果时返回 null
+handleCommand('lookup-word-online-structured', async (word) => {
+  if (!word || !word.trim()) {
+    return null;
+  }
+  let fetched;
+  try {
+    fetched = await fetchOnlineEntry(word, true);
+  } catch (error) {
+    if (error instanceof CommandError) {
+      throw error;
+    }
+    console.error('Online lookup error:', error);
+    throw new CommandError(ErrorCodes.NETWORK, error.message);
+  }
+  const { entry, stale } = fetched;
+  return entry ? { word: entry.word, source: onlineProvider.label, stale, entries: entry.entries } : null;
+});
+
+// 在线搜索只保留最新的请求：新请求到来时取消仍在进行的旧请求，被取消的请求返回 null
+let onlineSearchController = null;
+
+handleCommand('search-online', async (query) => {
+  if (offlineMode) {
+    throw new CommandError(ErrorCodes.OFFLINE, 'Online lookup is disabled in offline mode');
+  }
+  if (onlineSearchController) {
+    onlineSearchController.abort();
+  }
+  const controller = new AbortController();
+  onlineSearchController = controller;
+
+  try {
+    return await onlineProvider.search(query, controller.signal);
+  } catch (error) {
+    if (controller.signal.aborted) {
+      return null;
+    }
+    console.error('Online search error:', error);
+    throw new CommandError(ErrorCodes.NETWORK, error.message);
+  } finally {
+    if (onlineSearchController === controller) {
+      onlineSearchController = null;
+    }
+  }
+});
+
+ipcMain.handle('clear-online-cache', () => {
+  onlineCache.clear();
+  return true;
+});
+
+ipcMain.on('set-online-cache-ttl', (event, hours) => {
+  onlineCache.setTtlHours(saveSetting('onlineCacheTtlHours', hours));
+});
+
+ipcMain.on('set-online-cache-compress', (event, enabled) => {
+  onlineCache.setCompress(saveSetting('onlineCacheCompress', enabled));
+});
+
+ipcMain.on('set-online-timeout', (event, seconds) => {
+  onlineTimeoutSeconds = saveSetting('onlineTimeoutSeconds', seconds);
+  setRequestTimeout(onlineTimeoutSeconds);
+});
+
+// 同时进行的在线请求数上限，超出的请求排队
+ipcMain.on('set-online-concurrency', (event, limit) => {
+  onlineMaxConcurrent = saveSetting('onlineMaxConcurrent', limit);
+  setRequestConcurrency(onlineMaxConcurrent);
+});
+
+ipcMain.on('set-online-provider', (event, name) => {
+  onlineProvider = createProvider(saveSetting('onlineProvider', name), { language: onlineLanguage });
+});
+
+// 设置在线词典的语言（dictionaryapi.dev 的词条语言），不支持的语言按英文处理
+ipcMain.on('set-online-language', (event, language) => {
+  onlineLanguage = saveSetting('onlineLanguage', language);
+  onlineProvider = createProvider(onlineProvider.name, { language: onlineLanguage });
+});
+
+// 显示被截断词条的完整内容
+ipcMain.on('lookup-word-full', () => {
+  const result = dictionary ? dictionary.renderCurrentFull() : null;
+  if (result && lookupWindow && lookupWindow.webContents) {
+    lookupWindow.webContents.send('lookup-result', {
+      word: dictionary.lastEntry.word,
+      result: result
+    });
+  }
+});
+
+// 只显示当前词条中指定词性的部分（pos 为空时显示全部）
+ipcMain.on('filter-pos', (event, pos) => {
+  if (!dictionary || !dictionary.lastEntry) {
+    return;
+  }
+
+  dictionary.lastEntry.pos = pos || null;
+  if (lookupWindow && lookupWindow.webContents) {
+    lookupWindow.webContents.send('lookup-result', {
+      word: dictionary.lastEntry.word,
+      result: dictionary.restyleCurrent()
+    });
+  }
+});
+
+ipcMain.on('open-settings', () => {
+  createSettingsWindow();
+});
+
+// 将显示设置应用到解析器和所有查询窗口，并用新设置重新渲染当前词条
+// 当前配色的各项颜色，供查询窗口设置页面底色
+function getThemeColors() {
+  return MdictParser.resolveTheme(displaySettings.theme, displaySettings.customTheme);
+}
+
+function applyDisplaySettings() {
+  if (dictionary) {
+    dictionary.updateDisplaySettings(displaySettings);
+  }
+  sourceToggle.clear();
+
+  const settings = { ...displaySettings, themeColors: getThemeColors() };
+  BrowserWindow.getAllWindows().forEach(win => {
+    if (win !== mainWindow && win !== settingsWindow) {
+      win.webContents.send('update-display-settings', settings);
+    }
+  });
+
+  const restyled = dictionary ? dictionary.restyleCurrent() : null;
+  if (restyled && lookupWindow && lookupWindow.webContents) {
+    lookupWindow.webContents.send('lookup-result', {
+      word: dictionary.lastEntry.word,
+      result: restyled
+    });
+  }
+}
+
+// 同步获取显示设置
+ipcMain.on('get-display-settings', (event) => {
+  event.returnValue = {
+    ...displaySettings,
+    themeColors: getThemeColors(),
+    clipboardMonitor: clipboardMonitorEnabled,
+    clipboardMaxWords: clipboardMaxWords,
+    clipboardMaxLength: clipboardMaxLength,
+    hotkeyTarget: hotkeyTarget,
+    cycleDictionaryHotkey: cycleDictionaryHotkey,
+    autoReloadOnChange: autoReloadOnChange,
+    autoSpeak: autoSpeak,
+    offlineMode: offlineMode,
+    caseInsensitiveLookup: searchOptions.caseInsensitiveLookup,
+    ignoreAccents: searchOptions.ignoreAccents,
+    briefLength: searchOptions.briefLength,
+    briefFirstSentence: searchOptions.briefFirstSentence,
+    queryTrimChars: searchOptions.queryTrimChars,
+    onlineProvider: onlineProvider.name,
+    onlineCacheTtlHours: onlineCache.ttlHours,
+    onlineCacheCompress: onlineCache.compress,
+    onlineLanguage: onlineLanguage,
+    onlineTimeoutSeconds: onlineTimeoutSeconds,
+    onlineMaxConcurrent: onlineMaxConcurrent
+  };
+});
+
+// 设置字体
+ipcMain.on('set-font-family', (event, fontFamily) => {
+  displaySettings.fontFamily = saveSetting('fontFamily', fontFamily);
+
+  applyDisplaySettings();
+});
+
+// 设置字号
+ipcMain.on('set-font-size', (event, fontSize) => {
+  displaySettings.fontSize = saveSetting('fontSize', fontSize);
+
+  applyDisplaySettings();
+});
+
+// 设置行高
+ipcMain.on('set-line-height', (event, lineHeight) => {
+  displaySettings.lineHeight = saveSetting('lineHeight', lineHeight);
+
+  applyDisplaySettings();
+});
+
+// 设置后备字体
+ipcMain.on('set-font-fallbacks', (event, fontFallbacks) => {
+  displaySettings.fontFallbacks = saveSetting('fontFallbacks', fontFallbacks);
+
+  applyDisplaySettings();
+});
+
+ipcMain.on('speak-word', (event, word) => {
+  if (word && word.trim()) {
+    speakWord(word.trim());
+  }
+});
+
+ipcMain.on('set-offline-mode', (event, enabled) => {
+  offlineMode = saveSetting('offlineMode', enabled);
+  if (offlineMode && onlineSearchController) {
+    onlineSearchController.abort();
+  }
+});
+
+ipcMain.on('set-auto-speak', (event, enabled) => {
+  autoSpeak = saveSetting('autoSpeak', enabled);
+});
+
+// 设置是否显示词典来源标记
+ipcMain.on('set-show-source-badge', (event, enabled) => {
+  displaySettings.showSourceBadge = saveSetting('showSourceBadge', enabled);
+
+  applyDisplaySettings();
+});
+
+// 设置是否显示“重定向自”提示
+ipcMain.on('set-show-redirect-banner', (event, enabled) => {
+  displaySettings.showRedirectBanner = saveSetting('showRedirectBanner', enabled);
+
+  applyDisplaySettings();
+});
+
+// 设置配色方案（light / dark / custom），custom 时 customTheme 为各项颜色，缺少的取浅色预设
+ipcMain.on('set-theme', (event, theme, customTheme) => {
+  displaySettings.theme = saveSetting('theme', theme);
+  if (customTheme !== undefined) {
+    displaySettings.customTheme = saveSetting('customTheme', customTheme);
+  }
+
+  applyDisplaySettings();
+});
+
+// 设置首选发音地区（us / uk / auto）
+ipcMain.on('set-preferred-region', (event, region) => {
+  displaySettings.preferredRegion = saveSetting('preferredRegion', region);
+
+  applyDisplaySettings();
+});
+
+// 切换资源解析调试标注
+ipcMain.on('toggle-debug-resources', (event, enabled) => {
+  displaySettings.debugResources = saveSetting('debugResources', enabled);
+
+  applyDisplaySettings();
+});
+
+// 设置当前词典的覆盖项（如 unescapeEntities）
+ipcMain.on('set-dictionary-override', (event, name, value) => {
+  setDictionaryOverride(name, value);
+});
+
+// 查看词典的覆盖项（默认当前词典）
+ipcMain.handle('get-dictionary-overrides', (event, name) => {
+  return getDictionaryOverrides(name || getDictionaryKey());
+});
+
+ipcMain.handle('reset-dictionary-overrides', (event, name) => {
+  resetDictionaryOverrides(name || getDictionaryKey());
+  return getDictionaryOverrides(name || getDictionaryKey());
+});
+
+ipcMain.on('set-hotkey', (event, hotkey) => {
+  hotkey = canonicalizeHotkey(hotkey) || hotkey;
+  const { success, error } = registerGlobalHotkey(hotkey);
+
+  [mainWindow, settingsWindow].forEach(win => {
+    if (!win) {
+      return;
+    }
+    if (success) {
+      win.webContents.send('hotkey-updated', hotkey);
+    } else {
+      win.webContents.send('hotkey-error', { hotkey, error, current: currentHotkey });
+    }
+  });
+});
+
+// 设置切换主词典的快捷键，空字符串表示不使用；返回 { hotkey, registered }
+handleCommand('set-cycle-dictionary-hotkey', async (hotkey) => {
+  const canonical = hotkey ? canonicalizeHotkey(hotkey) : '';
+  if (canonical === null) {
+    throw new CommandError(ErrorCodes.HOTKEY_INVALID, `"${hotkey}" is not a supported key combination`);
+  }
+  if (canonical && canonical === canonicalizeHotkey(currentHotkey)) {
+    throw new CommandError(ErrorCodes.HOTKEY_UNAVAILABLE, `${canonical} is already the lookup hotkey`);
+  }
+
+  cycleDictionaryHotkey = canonical;
+  store.set('cycleDictionaryHotkey', cycleDictionaryHotkey);
+  registerGlobalHotkey(currentHotkey);
+  const accelerator = hotkeyToAccelerator(cycleDictionaryHotkey);
+  return { hotkey: cycleDictionaryHotkey, registered: accelerator ? globalShortcut.isRegistered(accelerator) : false };
+});
+
+// 把下一部词典设为主词典，返回它的名称（只有一部词典时返回 null）
+handleCommand('cycle-dictionary', async () => {
+  return cycleDictionary(await requireDictionary());
+});
+
+ipcMain.on('set-hotkey-target', (event, target) => {
+  hotkeyTarget = saveSetting('hotkeyTarget', target);
+  registerGlobalHotkey(currentHotkey);
+});
+
+ipcMain.on('toggle-auto-reload', (event, enabled) => {
+  autoReloadOnChange = saveSetting('autoReloadOnChange', enabled);
+
+  if (autoReloadOnChange) {
+    startDictionaryWatcher();
+  } else {
+    stopDictionaryWatcher();
+  }
+});
+
+ipcMain.on('toggle-clipboard-monitor', (event, enabled) => {
+  clipboardMonitorEnabled = saveSetting('clipboardMonitor', enabled);
+
+  if (clipboardMonitorEnabled) {
+    startClipboardMonitor();
+  } else {
+    stopClipboardMonitor();
+  }
+});
+
+// 剪贴板查询的最大单词数
+ipcMain.on('set-clipboard-max-words', (event, maxWords) => {
+  clipboardMaxWords = saveSetting('clipboardMaxWords', maxWords);
+});
+
+// 剪贴板查询的最大字符数
+ipcMain.on('set-clipboard-max-length', (event, maxLength) => {
+  clipboardMaxLength = saveSetting('clipboardMaxLength', maxLength);
+});
+
+// 按 config.js 中的规则校验设置项后保存，返回保存的值；normalize-config 使用同一套规则
+function saveSetting(field, value) {
+  const normalized = normalizeField(field, value);
+  store.set(field, normalized);
+  return normalized;
+}
+
+// 查询历史
+ipcMain.handle('get-history', () => {
+  return history.list();
+});
+
+ipcMain.handle('clear-history', () => {
+  history.clear();
+  return true;
+});
+
+ipcMain.on('set-history-size', (event, size) => {
+  history.setMaxEntries(saveSetting('historySize', size));
+});
+
+// 收藏
+ipcMain.handle('list-favorites', () => {
+  return favorites.list();
+});
+
+ipcMain.handle('add-favorite', (event, word, note) => {
+  const caseSensitive = dictionary ? dictionary.isKeyCaseSensitive() : false;
+  const source = dictionary ? dictionary.getDictionaryName() : '';
+  return favorites.add(word, source, note || '', caseSensitive);
+});
+
+ipcMain.handle('remove-favorite', (event, word) => {
+  return favorites.remove(word, dictionary ? dictionary.isKeyCaseSensitive() : false);
+});
+
+// 收藏导入导出的文件格式由扩展名决定：.csv / .xml（欧路词典生词本），其余按每行一个词处理
+function favoritesFormatOf(filePath) {
+  const extension = path.extname(filePath).slice(1).toLowerCase();
+  return Favorites.FORMATS.includes(extension) ? extension : 'txt';
+}
+
+// 导入收藏并与现有收藏合并（已收藏的词跳过）。没有指定文件时弹出文件选择框，取消时返回 null；
+// 返回 { path, added, duplicates, skipped }，skipped 为格式错误而跳过的行数
+handleCommand('import-favorites', async (filePath = null) => {
+  if (!filePath) {
+    const { canceled, filePaths } = await dialog.showOpenDialog({
+      properties: ['openFile'],
+      filters: [{ name: 'Word Lists', extensions: ['txt', 'csv', 'xml'] }]
+    });
+    if (canceled || filePaths.length === 0) {
+      return null;
+    }
+    filePath = filePaths[0];
+  }
+
+  let text;
+  try {
+    text = await fs.promises.readFile(filePath, 'utf-8');
+  } catch (error) {
+    throw new CommandError(ErrorCodes.FILE_ERROR, error.message);
+  }
+  const { entries, skipped } = Favorites.parse(text, favoritesFormatOf(filePath));
+  const added = favorites.importEntries(entries, '', dictionary ? dictionary.isKeyCaseSensitive() : false);
+  return { path: filePath, added, duplicates: entries.length - added, skipped };
+});
+
+// 导出收藏（txt / csv / xml），没有指定文件时弹出保存对话框，取消时返回 null；返回 { path, count }
+handleCommand('export-favorites', async (format = 'txt', filePath = null) => {
+  if (!Favorites.FORMATS.includes(format)) {
+    format = 'txt';
+  }
+  if (!filePath) {
+    const { canceled, filePath: chosenPath } = await dialog.showSaveDialog({
+      defaultPath: `favorites.${format}`,
+      filters: [{ name: `${format.toUpperCase()} Word List`, extensions: [format] }]
+    });
+    if (canceled || !chosenPath) {
+      return null;
+    }
+    filePath = chosenPath;
+  }
+
+  const entries = favorites.list();
+  try {
+    await fs.promises.writeFile(filePath, Favorites.format(entries, format), 'utf-8');
+  } catch (error) {
+    throw new CommandError(ErrorCodes.FILE_ERROR, error.message);
+  }
+  return { path: filePath, count: entries.length };
+});
+
+// 获取MDD资源
+ipcMain.handle('get-mdd-resource', async (event, resourceName) => {
+  if (!dictionary) {
+    return null;
+  }
+
+  try {
+    return await loadResource(resourceName);
   } catch (error) {
     console.error('Failed to get resource:', error);
     return null;
   }
 });
 
-// 注册自定义协议处理MDD资源
-function registerMddProtocol() {
-  protocol.registerBufferProtocol('mdd-resource', (request, callback) => {
-    const resourceName = request.url.replace('mdd-resource://', '');
-
-    // 从缓存获取
-    const cacheKey = `mdd:${resourceName}`;
-    if (resourceCache.has(cacheKey)) {
-      const resource = resourceCache.get(cacheKey);
-
-      // 确定MIME类型
-      let mimeType = 'application/octet-stream';
-      if (resourceName.endsWith('.png')) {
-        mimeType = 'image/png';
-      } else if (resourceName.endsWith('.jpg') || resourceName.endsWith('.jpeg')) {
-        mimeType = 'image/jpeg';
-      } else if (resourceName.endsWith('.gif')) {
-        mimeType = 'image/gif';
-      } else if (resourceName.endsWith('.svg')) {
-        mimeType = 'image/svg+xml';
-      } else if (resourceName.endsWith('.mp3')) {
-        mimeType = 'audio/mpeg';
-      } else if (resourceName.endsWith('.wav')) {
-        mimeType = 'audio/wav';
-      } else if (resourceName.endsWith('.ogg')) {
-        mimeType = 'audio/ogg';
-      } else if (resourceName.endsWith('.css')) {
-        mimeType = 'text/css';
-      } else if (resourceName.endsWith('.js')) {
-        mimeType = 'application/javascript';
+// 获取词典元数据（标题、描述、作者等）
+ipcMain.handle('get-dictionary-metadata', () => {
+  return dictionary ? dictionary.getMetadata() : null;
+});
+
+// 获取所有已加载词典的信息（标题、版本、编码、词条数等），用于“关于词典”面板
+ipcMain.handle('get-dictionary-info', () => {
+  return dictionary ? dictionary.getInfo() : [];
+});
+
+// 每日一词：以当天日期为随机种子，同一天总是同一个词，并记录到查询历史
+handleCommand('word-of-the-day', async () => {
+  const manager = await requireDictionary();
+  const now = new Date();
+  const today = `${now.getFullYear()}-${now.getMonth() + 1}-${now.getDate()}`;
+  const entry = manager.randomEntry(MdictParser.seededRandom(`${today}:${manager.getDictionaryName()}`));
+  if (!entry) {
+    return null;
+  }
+
+  history.add(entry.word, entry.source);
+  return { word: entry.word, source: entry.source, date: today };
+});
+
+// 清空资源缓存和所有词典的渲染、搜索缓存
+ipcMain.handle('clear-caches', () => {
+  clearResourceCache();
+  if (dictionary) {
+    dictionary.clearCaches();
+  }
+  return true;
+});
+
+// 配置方案：每个方案是一组词典路径和显示设置
+ipcMain.handle('list-profiles', () => {
+  return profiles.list();
+});
+
+// 以方案名执行操作，名称无效或操作不允许时返回 InvalidProfile 错误
+function profileCommand(action) {
+  return name => {
+    if (typeof name !== 'string' || !name.trim()) {
+      throw new CommandError(ErrorCodes.INVALID_PROFILE, 'Profile name is empty');
+    }
+    try {
+      action(name.trim());
+    } catch (error) {
+      throw new CommandError(ErrorCodes.INVALID_PROFILE, error.message);
+    }
+    return profiles.list();
+  };
+}
+
+handleCommand('create-profile', profileCommand(name => profiles.create(name)));
+handleCommand('delete-profile', profileCommand(name => profiles.delete(name)));
+
+// 切换方案：重新解析词典路径和显示设置，再重新加载词典
+handleCommand('switch-profile', async (name) => {
+  const list = profileCommand(target => profiles.switch(target))(name);
+
+  refreshDictionaryConfigs();
+  displaySettings = readDisplaySettings();
+  applyDisplaySettings();
+  await reloadDictionary();
+  return list;
+});
+
+// 手动重新加载词典（词典文件或样式表在外部被修改后），返回重新加载后的词典信息
+handleCommand('reload-dictionary', async () => {
+  refreshDictionaryConfigs();
+  const manager = await reloadDictionary();
+  if (!manager) {
+    throw dictionaryError;
+  }
+  return manager.getInfo();
+});
+
+// 查询类命令的返回值：成功时为 { result }，失败时为 { error: { code, message } }
+function handleCommand(channel, handler) {
+  ipcMain.handle(channel, async (event, ...args) => {
+    try {
+      return { result: await handler(...args) };
+    } catch (error) {
+      if (error instanceof CommandError) {
+        return { error: error.toJSON() };
       }
+      throw error;
+    }
+  });
+}
 
-      callback({
-        mimeType: mimeType,
-        data: Buffer.from(resource)
-      });
-    } else {
-      // 如果没有缓存，尝试异步加载
-      dictionary.getResource(resourceName).then(resource => {
-        if (resource) {
-          resourceCache.set(cacheKey, resource);
-
-          let mimeType = 'application/octet-stream';
-          if (resourceName.endsWith('.png')) mimeType = 'image/png';
-          else if (resourceName.endsWith('.jpg') || resourceName.endsWith('.jpeg')) mimeType = 'image/jpeg';
-          else if (resourceName.endsWith('.gif')) mimeType = 'image/gif';
-          else if (resourceName.endsWith('.mp3')) mimeType = 'audio/mpeg';
-          else if (resourceName.endsWith('.wav')) mimeType = 'audio/wav';
-          else if (resourceName.endsWith('.ogg')) mimeType = 'audio/ogg';
-          else if (resourceName.endsWith('.css')) mimeType = 'text/css';
-          else if (resourceName.endsWith('.js')) mimeType = 'application/javascript';
-
-          callback({
-            mimeType: mimeType,
-            data: Buffer.from(resource)
-          });
-        } else {
-          callback({ error: -2 }); // 找不到资源
-        }
-      }).catch(() => {
-        callback({ error: -6 }); // 加载失败
-      });
+// 确保词典已加载（需要时等待加载完成），否则抛出加载失败的原因
+async function requireDictionary() {
+  const manager = await ensureDictionary();
+  if (!manager) {
+    throw dictionaryError;
+  }
+  return manager;
+}
+
+// 预览查询在当前词典规则下的规范化结果
+handleCommand('normalized-query', async (word) => {
+  return (await requireDictionary()).normalizeQuery(word);
+});
+
+// 前缀搜索
+handleCommand('prefix-search', async (prefix, limit) => {
+  return (await requireDictionary()).prefixSearch(prefix, limit);
+});
+
+// 搜索词头：前缀匹配，结果不足时补充模糊匹配
+handleCommand('search-words', async (query, limit) => {
+  const manager = await requireDictionary();
+  const results = manager.searchWords(query, limit);
+  return searchOptions.briefLength > 0 ? manager.addBriefs(results) : results;
+});
+
+// 按来源分组的搜索结果 [{ label, online, results }]；includeOnline 为 true 且不在离线模式时附加在线结果。
+// 在线搜索失败时仍返回本地结果，在线分组中只有一个 unavailable 标记的结果，界面据此提示在线词典不可用
+handleCommand('search-words-grouped', async (query, limit, includeOnline = false) => {
+  const manager = await requireDictionary();
+  let results = manager.searchWords(query, limit);
+  if (searchOptions.briefLength > 0) {
+    results = manager.addBriefs(results);
+  }
+  if (includeOnline && !offlineMode) {
+    try {
+      results = results.concat(await onlineProvider.search(query));
+    } catch (error) {
+      console.warn('Online search failed:', error.message);
+      results.push({ word: query, source: onlineProvider.label, online: true, unavailable: true, error: error.message });
+    }
+  }
+  return manager.groupResults(results);
+});
+
+// 分页浏览以 query 开头的词头：返回 { results, hasMore, totalEstimate }，按字母顺序，翻页时顺序不变
+handleCommand('search-words-paged', async (query, offset = 0, limit = 50) => {
+  const manager = await requireDictionary();
+  const page = manager.prefixPage(query, Math.max(0, parseInt(offset) || 0), Math.min(Math.max(1, parseInt(limit) || 50), 500));
+  if (searchOptions.briefLength > 0) {
+    page.results = manager.addBriefs(page.results);
+  }
+  return page;
+});
+
+// 在释义正文中全文搜索；扫描大词典较慢，通过 search-definitions-progress 事件报告进度
+ipcMain.handle('search-definitions', async (event, query, limit) => {
+  try {
+    const manager = await requireDictionary();
+    const result = await manager.fulltextSearch(query, limit, (scanned, total) => {
+      if (!event.sender.isDestroyed()) {
+        event.sender.send('search-definitions-progress', { query, scanned, total });
+      }
+    });
+    return { result };
+  } catch (error) {
+    if (error instanceof CommandError) {
+      return { error: error.toJSON() };
     }
+    throw error;
+  }
+});
+
+// 查询选项：忽略大小写 / 忽略重音，以及搜索建议中简短释义的长度和是否只取第一句
+ipcMain.on('set-lookup-option', (event, name, value) => {
+  if (!['caseInsensitiveLookup', 'ignoreAccents', 'briefLength', 'briefFirstSentence', 'queryTrimChars'].includes(name)) {
+    return;
+  }
+  searchOptions[name] = saveSetting(name, value);
+  if (dictionary) {
+    dictionary.updateSearchOptions(searchOptions);
+  }
+});
+
+// 使用指定的词头变换（identity / stripArticles / stripPunctuation）重建前缀索引
+ipcMain.handle('rebuild-prefix-index', async (event, transformName) => {
+  if (!MdictParser.KEY_TRANSFORMS[transformName]) {
+    return null;
+  }
+
+  searchOptions.prefixTransform = transformName;
+  store.set('prefixTransform', transformName);
+
+  const manager = await ensureDictionary();
+  if (!manager) {
+    return null;
+  }
+  manager.updateSearchOptions(searchOptions);
+  return manager.buildPrefixIndex(transformName);
+});
+
+// 校验并修正所有配置项，返回修正记录
+ipcMain.handle('normalize-config', () => {
+  const corrections = normalizeConfig(store);
+
+  if (corrections.length > 0) {
+    Object.keys(displaySettings).forEach(key => {
+      if (store.has(key)) {
+        displaySettings[key] = store.get(key);
+      }
+    });
+    Object.keys(searchOptions).forEach(key => {
+      if (store.has(key)) {
+        searchOptions[key] = store.get(key);
+      }
+    });
+    if (dictionary) {
+      dictionary.updateSearchOptions(searchOptions);
+    }
+    applyDisplaySettings();
+
+    if (store.get('hotkey') !== currentHotkey) {
+      registerGlobalHotkey(store.get('hotkey'));
+    }
+  }
+
+  return corrections;
+});
+
+// 查询性能测试：对词表重复查询，统计吞吐量和延迟
+// 读取渲染缓存和资源缓存的命中 / 未命中次数，用于计算基准测试期间的缓存命中率
+function cacheCounters(manager) {
+  const caches = [resourceCache, ...manager.dictionaries.map(parser => parser.renderCache)];
+  return caches.reduce((counters, cache) => ({
+    hits: counters.hits + cache.hits,
+    misses: counters.misses + cache.misses
+  }), { hits: 0, misses: 0 });
+}
+
+// 基准测试的最大重复次数，超过时按上限执行
+const MAX_BENCHMARK_ITERATIONS = 1000;
+
+handleCommand('benchmark-lookups', async (words, iterations = 1) => {
+  if (!Array.isArray(words)) {
+    throw new CommandError(ErrorCodes.INVALID_ARGUMENT, 'words must be an array of strings');
+  }
+  if (typeof iterations !== 'number' || !Number.isFinite(iterations) || iterations < 1) {
+    throw new CommandError(ErrorCodes.INVALID_ARGUMENT, 'iterations must be a positive integer');
+  }
+  iterations = Math.min(Math.floor(iterations), MAX_BENCHMARK_ITERATIONS);
+  const manager = await requireDictionary();
+
+  const savedEntry = manager.lastEntry;
+  const latencies = [];
+  let coldMs = 0;
+  const countersBefore = cacheCounters(manager);
+  const startedAt = process.hrtime.bigint();
+
+  for (let i = 0; i < iterations; i++) {
+    for (const word of words) {
+      const begin = process.hrtime.bigint();
+      await manager.lookup(String(word));
+      const elapsed = Number(process.hrtime.bigint() - begin) / 1e6;
+      latencies.push(elapsed);
+      if (i === 0) {
+        coldMs += elapsed;
+      }
+    }
+  }
+
+  const totalMs = Number(process.hrtime.bigint() - startedAt) / 1e6;
+  manager.lastEntry = savedEntry;
+
+  const countersAfter = cacheCounters(manager);
+  const hits = countersAfter.hits - countersBefore.hits;
+  const misses = countersAfter.misses - countersBefore.misses;
+
+  const sorted = [...latencies].sort((a, b) => a - b);
+  const percentile = (p) => sorted.length ? sorted[Math.min(sorted.length - 1, Math.floor(sorted.length * p))] : 0;
+  const warmCount = latencies.length - words.length;
+
+  return {
+    lookups: latencies.length,
+    totalMs: totalMs,
+    lookupsPerSecond: totalMs > 0 ? latencies.length / (totalMs / 1000) : 0,
+    p50Ms: percentile(0.5),
+    p95Ms: percentile(0.95),
+    coldAvgMs: words.length ? coldMs / words.length : 0,
+    warmAvgMs: warmCount > 0 ? (latencies.reduce((a, b) => a + b, 0) - coldMs) / warmCount : null,
+    cacheHitRate: hits + misses > 0 ? hits / (hits + misses) : null
+  };
+});
+
+// 将 mdd-resource:// 引用替换为 data URI，使导出的 HTML 不依赖自定义协议
+async function inlineResources(html) {
+  const names = new Set();
+  html.replace(/mdd-resource:\/\/([^"'\s)]+)/g, (match, name) => names.add(name));
+
+  for (const name of names) {
+    const resourceName = MdictParser.resourceNameFromUrl(name);
+    const resource = await loadResource(resourceName);
+    if (resource) {
+      const dataUri = `data:${getMimeType(resourceName)};base64,${resource.toString('base64')}`;
+      html = html.split(`mdd-resource://${name}`).join(dataUri);
+    }
+  }
+  return html;
+}
+
+// 保存对话框的默认文件名：去掉路径分隔符和 Windows 不允许的字符，避开保留的设备名
+function safeFileName(name, extension) {
+  let base = String(name || '').replace(/[\\/:*?"<>|\x00-\x1f]/g, '_').replace(/[. ]+$/, '').trim().slice(0, 100);
+  if (!base) {
+    base = 'QuickDict';
+  } else if (/^(con|prn|aux|nul|com[1-9]|lpt[1-9])(\..*)?$/i.test(base)) {
+    base = `_${base}`;
+  }
+  return `${base}.${extension}`;
+}
+
+// 导出为 Anki 可导入的文本文件（制表符分隔，正面为单词，背面为释义 HTML）。
+// source 为 'favorites' 或 'history'；图片内嵌为 data URI，音频链接去掉，卡片不依赖词典文件
+ipcMain.handle('export-anki', async (event, source, deckName = 'QuickDict', filePath = null) => {
+  const manager = await ensureDictionary();
+  if (!manager) {
+    return null;
+  }
+
+  const entries = source === 'history' ? history.list() : favorites.list();
+  const words = [...new Set(entries.map(entry => entry.word))];
+
+  if (!filePath) {
+    const { canceled, filePath: chosenPath } = await dialog.showSaveDialog({
+      defaultPath: safeFileName(deckName, 'txt'),
+      filters: [{ name: 'Anki Text Import', extensions: ['txt'] }]
+    });
+    if (canceled || !chosenPath) {
+      return null;
+    }
+    filePath = chosenPath;
+  }
+
+  const lines = ['#separator:tab', '#html:true', `#deck:${deckName.replace(/[\r\n]/g, ' ')}`];
+  let skipped = 0;
+  for (const word of words) {
+    const body = await manager.lookupBody(word);
+    if (!body) {
+      skipped++;
+      continue;
+    }
+
+    const back = (await inlineResources(body.replace(/<a[^>]*data-audio="true"[^>]*>[\s\S]*?<\/a>/gi, '')))
+      .replace(/[\t\r\n]+/g, ' ')
+      .trim();
+    lines.push(`${word.replace(/[\t\r\n]+/g, ' ')}\t${back}`);
+  }
+
+  await fs.promises.writeFile(filePath, lines.join('\n') + '\n', 'utf-8');
+  return { path: filePath, exported: words.length - skipped, skipped };
+});
+
+// 检查词典文件能否使用（版本、编码、加密、压缩方式、词条数），不影响当前加载的词典。
+// 没有指定文件时弹出文件选择框，取消时返回 null
+handleCommand('validate-dictionary', async (filePath = null) => {
+  if (!filePath) {
+    const { canceled, filePaths } = await dialog.showOpenDialog({
+      properties: ['openFile'],
+      filters: [{ name: 'MDict Dictionary', extensions: ['mdx', 'mdd'] }]
+    });
+    if (canceled || filePaths.length === 0) {
+      return null;
+    }
+    filePath = filePaths[0];
+  }
+  return MdictParser.inspectFile(filePath);
+});
+
+// 导出所有词典的全部词头（txt / csv / json），可附带简短释义；
+// 大词典逐条写入文件，通过 export-wordlist-progress 事件报告进度。返回 { path, count }，取消时返回 null
+ipcMain.handle('export-wordlist', async (event, format, filePath = null, includeBriefs = false) => {
+  if (!WORDLIST_FORMATS.includes(format)) {
+    return null;
+  }
+  const manager = await ensureDictionary();
+  if (!manager) {
+    return null;
+  }
+
+  if (!filePath) {
+    const { canceled, filePath: chosenPath } = await dialog.showSaveDialog({
+      defaultPath: `${path.basename(dictionaryConfigs[0].mdx, path.extname(dictionaryConfigs[0].mdx))}.${format}`,
+      filters: [{ name: `${format.toUpperCase()} Wordlist`, extensions: [format] }]
+    });
+    if (canceled || !chosenPath) {
+      return null;
+    }
+    filePath = chosenPath;
+  }
+
+  const count = await exportWordlist(manager.dictionaries, format, filePath, {
+    includeBriefs,
+    onProgress: (written, total) => {
+      if (!event.sender.isDestroyed()) {
+        event.sender.send('export-wordlist-progress', { written, total });
+      }
+    }
+  });
+  return { path: filePath, count };
+});
+
+// 将单词的释义渲染为 PNG 图片
+ipcMain.handle('export-definition-image', async (event, word, filePath) => {
+  const manager = await ensureDictionary();
+  if (!manager) {
+    return null;
+  }
+
+  if (!filePath) {
+    const { canceled, filePath: chosenPath } = await dialog.showSaveDialog({
+      defaultPath: safeFileName(word, 'png'),
+      filters: [{ name: 'PNG Image', extensions: ['png'] }]
+    });
+    if (canceled || !chosenPath) {
+      return null;
+    }
+    filePath = chosenPath;
+  }
+
+  const savedEntry = manager.lastEntry;
+  const html = await inlineResources(await manager.lookup(word));
+  manager.lastEntry = savedEntry;
+
+  // 释义写入临时文件再加载：data: URL 有长度限制，大词条会加载失败
+  const tempDir = await fs.promises.mkdtemp(path.join(app.getPath('temp'), 'quickdict-'));
+  const htmlFile = path.join(tempDir, 'definition.html');
+  await fs.promises.writeFile(htmlFile, html, 'utf8');
+
+  const width = 600;
+  const offscreen = new BrowserWindow({
+    width: width,
+    height: 400,
+    show: false,
+    webPreferences: {
+      offscreen: true
+    }
+  });
+
+  try {
+    await offscreen.loadFile(htmlFile);
+    const height = await offscreen.webContents.executeJavaScript('document.documentElement.scrollHeight');
+    offscreen.setContentSize(width, Math.min(Math.max(height, 100), 8000));
+    // 等待调整尺寸后的重绘
+    await new Promise(resolve => setTimeout(resolve, 200));
+
+    const image = await offscreen.webContents.capturePage();
+    await fs.promises.writeFile(filePath, image.toPNG());
+    return filePath;
+  } catch (error) {
+    console.error('Failed to export definition image:', error);
+    return null;
+  } finally {
+    offscreen.destroy();
+    await fs.promises.rm(tempDir, { recursive: true, force: true }).catch(() => {});
+  }
+});
+
+// 健康检查：汇总应用状态，便于反馈问题
+ipcMain.handle('health-check', async () => {
+  const dataDir = app.getPath('userData');
+  let dataDirWritable = false;
+  try {
+    await fs.promises.access(dataDir, fs.constants.W_OK);
+    dataDirWritable = true;
+  } catch (error) {
+    dataDirWritable = false;
+  }
+
+  return {
+    version: app.getVersion(),
+    config: {
+      path: store.path,
+      loaded: fs.existsSync(store.path)
+    },
+    dictionary: dictionary ? dictionary.getStatus() : {
+      name: path.basename(dictionaryConfigs[0].mdx),
+      loaded: false,
+      fileExists: fs.existsSync(dictionaryConfigs[0].mdx)
+    },
+    cache: {
+      resources: resourceCache.size,
+      resourceBytes: resourceCache.bytes,
+      online: onlineCache.diskUsage()
+    },
+    hotkey: {
+      hotkey: currentHotkey,
+      registered: hotkeyToAccelerator(currentHotkey) ? globalShortcut.isRegistered(hotkeyToAccelerator(currentHotkey)) : false
+    },
+    clipboardMonitor: clipboardMonitorEnabled,
+    dataDir: {
+      path: dataDir,
+      writable: dataDirWritable
+    },
+    // 离线模式下不发起请求
+    online: {
+      offline: offlineMode,
+      providers: offlineMode ? [] : await probeProviders({ language: onlineLanguage })
+    }
+  };
+});
+
+// 比较两个词典的词头覆盖情况
+ipcMain.handle('coverage-diff', async (event, mdxFileA, mdxFileB) => {
+  try {
+    return MdictParser.coverageDiff(mdxFileA, mdxFileB);
+  } catch (error) {
+    console.error('Failed to compare dictionaries:', error);
+    return null;
+  }
+});
+
+// 比较同一个词在两个词典中的释义差异
+ipcMain.handle('definition-diff', async (event, word, mdxFileA, mdxFileB) => {
+  try {
+    // 只比较释义文本，不打开 MDD 分卷
+    const dictA = new MdictParser(mdxFileA, null, null, displaySettings);
+    const dictB = new MdictParser(mdxFileB, null, null, displaySettings);
+    await dictA.load({ resources: false });
+    await dictB.load({ resources: false });
+
+    const textA = dictA.lookupText(word);
+    const textB = dictB.lookupText(word);
+
+    return {
+      word,
+      foundInA: textA !== null,
+      foundInB: textB !== null,
+      segments: MdictParser.diffWords(textA || '', textB || '')
+    };
+  } catch (error) {
+    console.error('Failed to diff definitions:', error);
+    return null;
+  }
+});
+
+// 只返回资源的 MIME 类型，不读取资源内容（用于提前设置 <audio> 元素）
+ipcMain.handle('resource-mime', (event, resourceName) => {
+  return getMimeType(resourceName);
+});
+
+// 注册自定义协议处理MDD资源，支持 Range 请求以便音视频拖动进度
+function registerMddProtocol() {
+  protocol.handle('mdd-resource', async (request) => {
+    const resourceName = MdictParser.resourceNameFromUrl(request.url);
+
+    let data;
+    try {
+      data = await loadResource(resourceName);
+    } catch (error) {
+      console.error('Failed to load resource:', resourceName, error);
+      return new Response(null, { status: 500 });
+    }
+    if (!data) {
+      return new Response(null, { status: 404 });
+    }
+
+    const headers = {
+      'Content-Type': getMimeType(resourceName),
+      'Accept-Ranges': 'bytes'
+    };
+
+    const range = request.headers.get('Range');
+    const match = range ? range.match(/^bytes=(\d*)-(\d*)$/) : null;
+    if (!match) {
+      return new Response(data, { status: 200, headers: { ...headers, 'Content-Length': String(data.length) } });
+    }
+
+    // bytes=start-end、bytes=start-、bytes=-suffixLength
+    let start = match[1] ? parseInt(match[1]) : data.length - parseInt(match[2] || '0');
+    let end = match[1] && match[2] ? parseInt(match[2]) : data.length - 1;
+    start = Math.max(start, 0);
+    end = Math.min(end, data.length - 1);
+    if (start > end) {
+      return new Response(null, { status: 416, headers: { 'Content-Range': `bytes */${data.length}` } });
+    }
+
+    return new Response(data.subarray(start, end + 1), {
+      status: 206,
+      headers: {
+        ...headers,
+        'Content-Length': String(end - start + 1),
+        'Content-Range': `bytes ${start}-${end}/${data.length}`
+      }
+    });
   });
 }
 
+// 自定义协议需要在应用就绪前声明为可流式读取，音视频才能按范围请求
+protocol.registerSchemesAsPrivileged([
+  { scheme: 'mdd-resource', privileges: { stream: true, supportFetchAPI: true } }
+]);
+
 // 应用程序就绪
 app.whenReady().then(() => {
   // 注册MDD资源协议
@@ -435,6 +2058,11 @@ app.whenReady().then(() => {
     startClipboardMonitor();
   }
 
+  // 监听词典文件变化（如果启用）
+  if (autoReloadOnChange) {
+    startDictionaryWatcher();
+  }
+
   app.on('activate', () => {
     if (BrowserWindow.getAllWindows().length === 0) {
       createMainWindow();
@@ -446,6 +2074,7 @@ app.whenReady().then(() => {
 app.on('will-quit', () => {
   globalShortcut.unregisterAll();
   stopClipboardMonitor();
+  stopDictionaryWatcher();
 });
 
 app.on('window-all-closed', () => {
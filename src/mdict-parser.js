@@ -1,6 +1,7 @@
 const fs = require('fs');
 const path = require('path');
 const { MDX, MDD } = require('js-mdict');
+const ByteLruCache = require('./byte-cache');
 
 class MdictParser {
   constructor(mdxFile, mddFile, cssFile, displaySettings = {}) {
@@ -9,26 +10,218 @@ class MdictParser {
     this.cssFile = cssFile;
     this.mdx = null;
     this.mdd = null;
+    // 全部 MDD 分卷（dict.mdd、dict.1.mdd……），mdd 为第一卷
+    this.mdds = [];
     this.cssContent = '';
     this.displaySettings = displaySettings;
+    this.overrides = {};
+    this.lastEntry = null;
+    this.resourceAliases = null;
+    this.styleSheet = null;
+    this.levelCache = new Map();
+    this.searchOptions = {};
+    this.normalizedKeys = null;
+    this.foldedKeys = null;
+    this.homographs = null;
+    this.prefixIndex = null;
+    this.renderCache = new ByteLruCache(0);
+    this.renderCacheSettings = '';
+    this.fulltextCache = new Map();
+    // 拼音索引（PinyinIndex），只有在覆盖设置中启用 pinyinIndex 的词典才会建立
+    this.pinyinIndex = null;
   }
 
   updateDisplaySettings(settings) {
     this.displaySettings = settings;
   }
 
-  async load() {
-    await this.loadCSS();
+  updateSearchOptions(options) {
+    this.searchOptions = options || {};
+    this.foldedKeys = null;
+  }
+
+  // 获取词条列表（兼容 js-mdict 不同版本的字段名）
+  getKeywordList(source = this.mdx) {
+    if (!source) {
+      return [];
+    }
+    return source.keywordList || source.keyList || [];
+  }
+
+  // 读取某个词条项的释义（优先按记录位置读取，可区分同名词条）
+  fetchDefinition(item) {
+    if (typeof this.mdx.fetch_definition === 'function') {
+      return this.mdx.fetch_definition(item);
+    }
+    return this.mdx.lookup(item.keyText);
+  }
+
+  // 按词典顺序逐条遍历所有词条，每次只读取一条记录
+  *entries() {
+    if (!this.mdx) {
+      throw new Error('Dictionary not loaded');
+    }
+
+    for (const item of this.getKeywordList()) {
+      const result = this.fetchDefinition(item);
+      yield {
+        word: item.keyText,
+        definition: result && result.definition ? result.definition : ''
+      };
+    }
+  }
+
+  // 随机取一个词条（每个词头的概率相同），跳过 @@@LINK= 重定向；
+  // random 返回 [0, 1) 的数，词典为空或多次只取到重定向时返回 null
+  randomEntry(random = Math.random, attempts = 20) {
+    const list = this.getKeywordList();
+    for (let i = 0; list.length > 0 && i < attempts; i++) {
+      const item = list[Math.floor(random() * list.length)];
+      const result = this.fetchDefinition(item);
+      if (result && result.definition && !this.getRedirectTarget(result.definition)) {
+        return { word: item.keyText, definition: result.definition };
+      }
+    }
+    return null;
+  }
+
+  // 词典的基本状态
+  getStatus() {
+    const header = this.mdx ? this.mdx.header || {} : {};
+    return {
+      name: this.mdx ? this.getDictionaryName() : path.basename(this.mdxFile),
+      loaded: !!this.mdx,
+      entryCount: this.getKeywordList().length,
+      encoding: header.Encoding || (this.mdx && this.mdx.meta ? this.mdx.meta.encoding : '') || '',
+      version: header.GeneratedByEngineVersion || '',
+      hasResources: !!this.mdd,
+      hasStylesheet: !!this.cssContent
+    };
+  }
+
+  // 词典头部元数据：标题、描述，其余属性（Author、RegisterBy 等）放在 extra 中
+  getMetadata() {
+    const header = this.mdx && this.mdx.header ? this.mdx.header : {};
+    const extra = {};
+    Object.keys(header).forEach(key => {
+      if (key !== 'Title' && key !== 'Description' && typeof header[key] !== 'object') {
+        extra[key] = String(header[key]);
+      }
+    });
+
+    return {
+      title: this.getDictionaryName(),
+      description: header.Description || '',
+      author: header.Author || header.RegisterBy || header.Register_by || '',
+      extra: extra
+    };
+  }
+
+  // 词典信息：标题、描述、版本、编码、词条数和块数，用于“关于词典”面板。
+  // 块数来自 js-mdict 解析的键块 / 记录块头部，取不到时为 null
+  getInfo() {
+    const header = this.mdx && this.mdx.header ? this.mdx.header : {};
+    const keyHeader = this.mdx && this.mdx.keyHeader ? this.mdx.keyHeader : {};
+    const recordHeader = this.mdx && this.mdx.recordHeader ? this.mdx.recordHeader : {};
+    const countOf = value => (Number.isFinite(Number(value)) ? Number(value) : null);
+
+    return {
+      file: path.basename(this.mdxFile),
+      title: this.getDictionaryName(),
+      description: header.Description || '',
+      version: header.GeneratedByEngineVersion || '',
+      encoding: header.Encoding || (this.mdx && this.mdx.meta ? this.mdx.meta.encoding : '') || '',
+      creationDate: header.CreationDate || '',
+      entryCount: this.getKeywordList().length,
+      keyBlockCount: countOf(keyHeader.keywordBlocksNum),
+      recordBlockCount: countOf(recordHeader.recordBlocksNum),
+      hasResources: !!this.mdd
+    };
+  }
+
+  // 更新针对当前词典的覆盖设置
+  updateOverrides(overrides) {
+    this.overrides = overrides || {};
+    this.clearRenderCache();
+    // 等级选择器按词典覆盖配置，变化后之前提取的等级失效
+    this.levelCache.clear();
+  }
+
+  clearRenderCache() {
+    this.renderCache.clear();
+  }
+
+  // 清空所有由解析器维护的缓存（渲染、全文搜索、词汇等级）
+  clearCaches() {
+    this.clearRenderCache();
+    this.fulltextCache.clear();
+    this.levelCache.clear();
+  }
+
+  // 渲染缓存：按词条、是否完整显示和当前渲染设置缓存 HTML，设置变化时整体失效
+  getRenderCacheKey(entry, full = false) {
+    const settings = JSON.stringify([this.displaySettings, this.overrides]);
+    if (settings !== this.renderCacheSettings) {
+      this.clearRenderCache();
+      this.renderCacheSettings = settings;
+    }
+    return JSON.stringify([entry.word, entry.displayWord, !!entry.redirected, entry.pos || null, !!full]);
+  }
+
+  putRenderCache(key, html) {
+    this.renderCache.setMaxBytes(parseInt(this.displaySettings.renderCacheBytes) || 0);
+    this.renderCache.set(key, html);
+  }
+
+  // 词典名称：优先使用 MDX 头部的 Title，否则使用文件名
+  getDictionaryName() {
+    const title = this.mdx && this.mdx.header ? this.mdx.header.Title : '';
+    if (title && !/^Title \(No HTML code allowed\)$/i.test(title.trim())) {
+      return title.trim();
+    }
+    return path.basename(this.mdxFile, path.extname(this.mdxFile));
+  }
+
+  // options.resources 为 false 时只加载 MDX（不读取样式表和 MDD），用于只比较释义文本等场合
+  async load(options = {}) {
+    const resources = options.resources !== false;
+    if (resources) {
+      await this.loadCSS();
+    }
 
     try {
+      if (path.extname(this.mdxFile).toLowerCase() === '.mdd') {
+        throw new Error(`"${path.basename(this.mdxFile)}" is a resource file (MDD), not a dictionary (MDX)`);
+      }
+
       // 加载MDX文件
       console.log('Loading MDX file:', this.mdxFile);
+      MdictParser.verifyHeader(this.mdxFile);
       this.mdx = new MDX(this.mdxFile);
 
-      // 尝试加载MDD文件（资源文件）
-      if (this.mddFile && fs.existsSync(this.mddFile)) {
-        console.log('Loading MDD file:', this.mddFile);
-        this.mdd = new MDD(this.mddFile);
+      // 词头看起来都是资源路径时，说明把 MDD 当成了 MDX
+      if (this.looksLikeResourceKeys(this.getKeywordList())) {
+        this.mdx = null;
+        throw new Error(`"${path.basename(this.mdxFile)}" is a resource file (MDD), not a dictionary (MDX)`);
+      }
+      MdictParser.checkKeyBlocks(this.mdx, this.mdxFile, this.getKeywordList());
+      MdictParser.verifyRecordInfo(this.mdx, this.mdxFile, this.getKeywordList());
+
+      if (resources) {
+        if (this.mddFile && path.extname(this.mddFile).toLowerCase() === '.mdx') {
+          console.warn('Ignoring resource file, it is a dictionary (MDX):', this.mddFile);
+        } else {
+          // 大词典的资源可能分卷保存在 dict.mdd、dict.1.mdd、dict.2.mdd……中，按顺序全部加载
+          MdictParser.findMddFiles(this.mdxFile, this.mddFile).forEach(file => {
+            console.log('Loading MDD file:', file);
+            MdictParser.verifyHeader(file);
+            const mdd = new MDD(file);
+            MdictParser.checkKeyBlocks(mdd, file, this.getKeywordList(mdd));
+            MdictParser.verifyRecordInfo(mdd, file, this.getKeywordList(mdd));
+            this.mdds.push(mdd);
+          });
+          this.mdd = this.mdds[0] || null;
+        }
       }
 
       console.log('Dictionary loaded successfully');
@@ -39,6 +232,16 @@ class MdictParser {
     }
   }
 
+  // 抽样检查词头：绝大多数以路径分隔符开头并带有文件扩展名时视为资源键
+  looksLikeResourceKeys(list) {
+    const sample = list.slice(0, 50);
+    if (sample.length === 0) {
+      return false;
+    }
+    const resourceLike = sample.filter(item => /^[\\/].*\.[a-z0-9]{2,4}$/i.test(item.keyText)).length;
+    return resourceLike / sample.length > 0.8;
+  }
+
   async loadCSS() {
     try {
       if (fs.existsSync(this.cssFile)) {
@@ -50,134 +253,507 @@ class MdictParser {
     }
   }
 
-  async lookup(word) {
+  // guard 由一次用户操作共享，防止重定向或链接循环导致无限查询
+  async lookup(word, guard = MdictParser.createLookupGuard()) {
+    const section = await this.lookupSection(word, guard);
+    return section.entry ? this.wrapPage(section.html) : section.html;
+  }
+
+  // 查询并渲染词条正文（不含页面样式）；没有找到时 entry 为 null，html 为提示内容
+  async lookupSection(word, guard = MdictParser.createLookupGuard()) {
     if (!this.mdx) {
       throw new Error('Dictionary not loaded');
     }
 
+    if (!guard.visit(word, this.mdxFile)) {
+      return { entry: null, html: this.formatLookupLoop(word) };
+    }
+
     try {
       // 查找单词
-      let result = this.mdx.lookup(word);
+      let result = this.lookupKey(word);
 
       if (!result || !result.definition) {
-        return `<div class="not-found">
-          <h3>Not Found</h3>
-          <p>Word "<strong>${this.escapeHtml(word)}</strong>" not found in dictionary.</p>
-          <p style="color: #666; font-size: 12px; margin-top: 10px;">
-            Did you mean: ${this.getSuggestions(word)}
-          </p>
-        </div>`;
+        return { entry: null, html: this.formatNotFound(word) };
       }
 
-      // 获取定义内容
+      // 获取定义内容；标题和同词头记录使用实际匹配到的词头（如 "Hello," 匹配到 hello）
       let definition = result.definition;
-      let displayWord = word;
+      let displayWord = result.keyText || word;
+      let redirected = false;
 
-      // 处理 @@@LINK= 重定向
-      const linkMatch = definition.match(/@@@LINK=\s*(\S+)/i);
-      if (linkMatch) {
-        const targetWord = linkMatch[1];
-        console.log(`Redirecting: ${word} -> ${targetWord}`);
+      // 处理 @@@LINK= 重定向（可能连续多次，由 guard 限制次数并防止循环）
+      let targetWord = this.getRedirectTarget(definition);
+      while (targetWord) {
+        console.log(`Redirecting: ${displayWord} -> ${targetWord}`);
+
+        if (!guard.visit(targetWord, this.mdxFile)) {
+          return { entry: null, html: this.formatLookupLoop(targetWord) };
+        }
 
         // 查找目标词
-        const targetResult = this.mdx.lookup(targetWord);
-        if (targetResult && targetResult.definition) {
-          definition = targetResult.definition;
-          displayWord = targetWord;
-        } else {
-          // 如果目标词也没找到，显示重定向信息
-          return `<div class="not-found">
-            <h3>Redirect Failed</h3>
-            <p>Word "<strong>${this.escapeHtml(word)}</strong>" redirects to "<strong>${this.escapeHtml(targetWord)}</strong>", but the target word was not found.</p>
-          </div>`;
+        const targetResult = this.lookupKey(targetWord);
+        if (!targetResult || !targetResult.definition) {
+          // 如果目标词也没找到，按未找到处理
+          return { entry: null, html: this.formatNotFound(word, targetWord) };
         }
+
+        definition = targetResult.definition;
+        displayWord = targetResult.keyText || targetWord;
+        redirected = true;
+        targetWord = this.getRedirectTarget(definition);
       }
 
-      const htmlContent = this.processDefinition(definition, word);
-
-      // 构建完整的HTML
-      const fontFamily = this.displaySettings.fontFamily || 'Segoe UI';
-      const fontSize = this.displaySettings.fontSize || '14';
-      const lineHeight = this.displaySettings.lineHeight || '1.6';
-
-      return `
-        <!DOCTYPE html>
-        <html>
-        <head>
-          <meta charset="utf-8">
-          <style>
-            body {
-              font-family: '${fontFamily}', Tahoma, Geneva, Verdana, sans-serif;
-              padding: 10px;
-              margin: 0;
-              font-size: ${fontSize}px;
-              line-height: ${lineHeight};
-              color: #333;
-            }
-
-            h2 {
-              color: #2196F3;
-              border-bottom: 2px solid #2196F3;
-              padding-bottom: 5px;
-            }
-
-            .word-title {
-              font-size: ${parseInt(fontSize) + 4}px;
-              font-weight: bold;
-              color: #1976D2;
-              margin-bottom: 10px;
-            }
-
-            .redirect-info {
-              font-size: ${parseInt(fontSize) - 2}px;
-              color: #999;
-              margin-bottom: 10px;
-              font-style: italic;
-            }
-
-            ${this.cssContent}
-
-            img {
-              max-width: 100%;
-              height: auto;
-            }
-
-            table {
-              border-collapse: collapse;
-              max-width: 100%;
-              font-size: ${parseInt(fontSize) - 1}px;
-            }
-
-            a {
-              color: #1976D2;
-              text-decoration: none;
-            }
-
-            a:hover {
-              text-decoration: underline;
-            }
-          </style>
-        </head>
-        <body>
-          <div class="word-title">${this.escapeHtml(displayWord)}</div>
-          ${displayWord !== word ? `<div class="redirect-info">(redirected from "${this.escapeHtml(word)}")</div>` : ''}
-          ${htmlContent}
-        </body>
-        </html>
-      `;
+      // 同一词头下有多条记录（如名词和动词分开收录）时，按词典中的顺序全部显示
+      definition = this.collectHomographs(displayWord, definition);
+
+      // 缓存当前词条，便于显示设置变化时重新渲染
+      this.lastEntry = { word, displayWord, definition, redirected };
+
+      return { entry: this.lastEntry, html: this.renderEntryBody(this.lastEntry) };
     } catch (error) {
       console.error('Lookup error:', error);
-      return `<div class="error">
+      const message = MdictParser.isCorruptBlockError(error)
+        ? `Corrupt record block in ${path.basename(this.mdxFile)} (${error.message})`
+        : error.message;
+      return {
+        entry: null,
+        html: `<div class="error">
         <h3>Error</h3>
         <p>Failed to lookup word: ${this.escapeHtml(word)}</p>
-        <p style="color: #666; font-size: 12px;">${error.message}</p>
-      </div>`;
+        <p class="hint" style="font-size: 12px;">${this.escapeHtml(message)}</p>
+      </div>`
+      };
+    }
+  }
+
+  // 按词性过滤释义：以 class="pos" 标记为分段点，保留匹配的段落；
+  // 无法识别结构或没有匹配时返回完整内容
+  filterPos(html, pos) {
+    const pattern = /<(\w+)[^>]*class=["'][^"']*\bpos\b[^"']*["'][^>]*>([\s\S]*?)<\/\1>/gi;
+    const markers = [];
+    let match;
+    while ((match = pattern.exec(html)) !== null) {
+      markers.push({ index: match.index, pos: this.htmlToText(match[2]).toLowerCase() });
+    }
+
+    if (markers.length < 2) {
+      return html;
+    }
+
+    const wanted = pos.trim().toLowerCase();
+    let filtered = html.slice(0, markers[0].index);
+    let matched = false;
+    markers.forEach((marker, i) => {
+      if (marker.pos.startsWith(wanted)) {
+        const end = i + 1 < markers.length ? markers[i + 1].index : html.length;
+        filtered += html.slice(marker.index, end);
+        matched = true;
+      }
+    });
+
+    return matched ? filtered : html;
+  }
+
+  // 模板占位符：{word}、{definition}、{phonetic}
+  applyDefinitionTemplate(template, word, definition) {
+    const values = {
+      word: this.escapeHtml(word),
+      definition: definition,
+      phonetic: this.escapeHtml(this.extractPhonetic(definition) || '')
+    };
+    return template.replace(/\{(word|definition|phonetic)\}/g, (match, name) => values[name]);
+  }
+
+  // 提取音标：优先使用常见的音标 class，其次匹配 /.../ 或 [...] 形式
+  extractPhonetic(html) {
+    const classMatch = html.match(/<(\w+)[^>]*class=["'][^"']*\b(phon|phonetic|pron|ipa)\b[^"']*["'][^>]*>([\s\S]*?)<\/\1>/i);
+    if (classMatch) {
+      return this.htmlToText(classMatch[3]);
+    }
+
+    const text = this.htmlToText(html);
+    const slashMatch = text.match(/\/[^\/\s][^\/\n]{0,40}\/|\[[^\]\n]{1,40}\]/);
+    return slashMatch ? slashMatch[0] : null;
+  }
+
+  // 解析头部的 StyleSheet：每三行一组，依次为编号、开始片段、结束片段（首次使用时解析）
+  getStyleSheet() {
+    if (this.styleSheet) {
+      return this.styleSheet;
+    }
+
+    this.styleSheet = new Map();
+    let source = this.mdx && this.mdx.header ? this.mdx.header.StyleSheet || '' : '';
+    if (typeof source !== 'string') {
+      return this.styleSheet;
+    }
+    if (this.isDoubleEscaped(source)) {
+      source = this.unescapeHtml(source);
+    }
+
+    const lines = source.split(/\r?\n|\r/);
+    for (let i = 0; i + 2 < lines.length; i += 3) {
+      if (/^\d+$/.test(lines[i].trim())) {
+        this.styleSheet.set(lines[i].trim(), [lines[i + 1], lines[i + 2]]);
+      }
+    }
+    return this.styleSheet;
+  }
+
+  // 展开压缩样式：`N` 标记替换为对应的开始片段，并在下一个标记前（或末尾）补上结束片段
+  applyStyleSheet(definition) {
+    const styleSheet = this.getStyleSheet();
+    if (styleSheet.size === 0 || !/`\d+`/.test(definition)) {
+      return definition;
+    }
+
+    const parts = definition.split(/`(\d+)`/);
+    let html = parts[0];
+    let closing = '';
+    for (let i = 1; i < parts.length; i += 2) {
+      const style = styleSheet.get(parts[i]);
+      if (!style) {
+        html += '`' + parts[i] + '`' + parts[i + 1];
+        continue;
+      }
+      html += closing + style[0] + parts[i + 1];
+      closing = style[1];
+    }
+    return html + closing;
+  }
+
+  // 判断资源名或 class 属于哪个发音地区（us / uk）
+  detectRegion(text) {
+    if (/(^|[\s_\-\/\\])(us|am|n_am|ame|amer)([\s_\-.\d]|$)/i.test(text)) {
+      return 'us';
+    }
+    if (/(^|[\s_\-\/\\])(uk|gb|br|bre|brit)([\s_\-.\d]|$)/i.test(text)) {
+      return 'uk';
+    }
+    return null;
+  }
+
+  // 从释义的音频链接中选出首选地区的发音，没有匹配时返回第一个
+  selectRegionAudio(html) {
+    const region = this.displaySettings.preferredRegion || 'auto';
+    const links = [];
+    const pattern = /<a([^>]*)href=["']([^"']*\.(mp3|wav|ogg|spx))["']([^>]*)>/gi;
+    let match;
+    while ((match = pattern.exec(html)) !== null) {
+      const classMatch = (match[1] + match[4]).match(/class=["']([^"']*)["']/i);
+      links.push({
+        href: match[2],
+        region: this.detectRegion(path.basename(match[2])) || (classMatch ? this.detectRegion(classMatch[1]) : null)
+      });
+    }
+
+    if (links.length === 0) {
+      return null;
+    }
+    const preferred = region !== 'auto' ? links.find(link => link.region === region) : null;
+    return (preferred || links[0]).href;
+  }
+
+  // 词条的首选发音：MDD 中有对应音频时返回可播放的地址，否则返回 null
+  getPronunciationUrl(entry) {
+    const href = this.selectRegionAudio(entry.definition);
+    if (!href) {
+      return null;
+    }
+    if (/^https?:\/\//i.test(href)) {
+      return href;
+    }
+    const resourceName = path.basename(href).replace(/\\/g, '/');
+    return this.hasResource(resourceName) ? `mdd-resource://${resourceName}` : null;
+  }
+
+  // 隐藏非首选地区的发音块
+  getRegionCss() {
+    const region = this.displaySettings.preferredRegion;
+    if (region === 'us') {
+      return `${MdictParser.REGION_SELECTORS.uk.join(', ')} { display: none !important; }`;
+    }
+    if (region === 'uk') {
+      return `${MdictParser.REGION_SELECTORS.us.join(', ')} { display: none !important; }`;
+    }
+    return '';
+  }
+
+  // 重定向提示，文字可通过 redirectBannerText 配置（{word} 为原查询词）
+  formatRedirectBanner(word) {
+    const template = this.displaySettings.redirectBannerText || '(redirected from "{word}")';
+    const text = this.escapeHtml(template).replace(/\{word\}/g, this.escapeHtml(word));
+    return `<div class="redirect-info">${text}</div>`;
+  }
+
+  // 记录内容只是一个 @@@LINK= 重定向时返回目标词，否则返回 null
+  getRedirectTarget(definition) {
+    const match = definition.trim().match(/^@@@LINK=([^\r\n]+)/i);
+    return match ? match[1].replace(/\0/g, '').trim() : null;
+  }
+
+  formatNotFound(word, redirectTarget = null) {
+    const redirectNote = redirectTarget
+      ? `<p>It redirects to "<strong>${this.escapeHtml(redirectTarget)}</strong>", which is not in the dictionary either.</p>`
+      : '';
+    return `<div class="not-found">
+      <h3>Not Found</h3>
+      <p>Word "<strong>${this.escapeHtml(word)}</strong>" not found in dictionary.</p>
+      ${redirectNote}
+      <p class="hint" style="font-size: 12px; margin-top: 10px;">
+        Did you mean: ${this.getSuggestions(redirectTarget || word)}
+      </p>
+      <p style="font-size: 12px; margin-top: 10px;">
+        <a href="quickdict://online/${encodeURIComponent(word)}">Look up online</a>
+      </p>
+    </div>`;
+  }
+
+  formatLookupLoop(word) {
+    return `<div class="error">
+      <h3>Lookup Stopped</h3>
+      <p>Too many chained lookups while resolving "<strong>${this.escapeHtml(word)}</strong>". The dictionary may contain a reference loop.</p>
+    </div>`;
+  }
+
+  // 将词条渲染为完整的 HTML 页面；full 为 true 时不截断超长词条
+  renderEntry(entry, full = false) {
+    return this.wrapPage(this.renderEntryBody(entry, full));
+  }
+
+  // 渲染词条正文，结果按词条缓存
+  renderEntryBody(entry, full = false) {
+    const cacheKey = this.getRenderCacheKey(entry, full);
+    const cached = this.renderCache.get(cacheKey);
+    if (cached !== undefined) {
+      return cached;
+    }
+
+    const html = this.buildEntryBody(entry, full);
+    this.putRenderCache(cacheKey, html);
+    return html;
+  }
+
+  buildEntryBody(entry, full = false) {
+    const { word, displayWord, definition } = entry;
+    let htmlContent = this.processDefinition(definition, word);
+
+    // 按词典配置的模板包装词条（适用于只有纯文本释义的词典）
+    if (this.overrides.definitionTemplate) {
+      htmlContent = this.applyDefinitionTemplate(this.overrides.definitionTemplate, displayWord, htmlContent);
+    }
+
+    // 只显示指定词性的部分
+    if (entry.pos) {
+      htmlContent = this.filterPos(htmlContent, entry.pos);
+    }
+
+    // 超长词条先截断显示，避免窗口卡顿
+    const maxRenderBytes = parseInt(this.displaySettings.maxRenderBytes) || 0;
+    if (!full && maxRenderBytes > 0 && Buffer.byteLength(htmlContent) > maxRenderBytes) {
+      htmlContent = this.truncateHtml(htmlContent, maxRenderBytes) +
+        `<div class="show-full"><a href="quickdict://show-full">Show full entry</a></div>`;
+    }
+
+    return `
+        ${this.displaySettings.showSourceBadge ? `<div class="source-badge">${this.escapeHtml(this.getDictionaryName())}</div>` : ''}
+        <div class="word-title">${this.escapeHtml(displayWord)}</div>
+        ${entry.redirected && this.displaySettings.showRedirectBanner !== false ? this.formatRedirectBanner(word) : ''}
+        ${htmlContent}
+    `;
+  }
+
+  // 用页面框架和样式包装正文，cssContent 默认为本词典的样式表
+  wrapPage(body, cssContent = this.cssContent) {
+    // 构建完整的HTML
+    const fontFamily = this.getFontFamilyStack();
+    const fontSize = this.displaySettings.fontSize || '14';
+    const lineHeight = this.displaySettings.lineHeight || '1.6';
+    const colors = MdictParser.resolveTheme(this.displaySettings.theme, this.displaySettings.customTheme);
+
+    return `
+      <!DOCTYPE html>
+      <html>
+      <head>
+        <meta charset="utf-8">
+        <style>
+          body {
+            font-family: ${fontFamily};
+            padding: 10px;
+            margin: 0;
+            font-size: ${fontSize}px;
+            line-height: ${lineHeight};
+            color: ${colors.foreground};
+            background: ${colors.background};
+          }
+
+          h2 {
+            color: ${colors.accent};
+            border-bottom: 2px solid ${colors.accent};
+            padding-bottom: 5px;
+          }
+
+          .word-title {
+            font-size: ${parseInt(fontSize) + 4}px;
+            font-weight: bold;
+            color: ${colors.link};
+            margin-bottom: 10px;
+          }
+
+          .source-badge {
+            display: inline-block;
+            font-size: ${parseInt(fontSize) - 3}px;
+            color: ${colors.background};
+            background: ${colors.muted};
+            border-radius: 3px;
+            padding: 1px 6px;
+            margin-bottom: 8px;
+          }
+
+          .show-full {
+            margin: 15px 0;
+            padding-top: 10px;
+            border-top: 1px dashed ${colors.border};
+            text-align: center;
+          }
+
+          .resource-missing {
+            font-size: ${parseInt(fontSize) - 3}px;
+            color: #c62828;
+            background: #ffebee;
+            padding: 0 4px;
+          }
+
+          .redirect-info {
+            font-size: ${parseInt(fontSize) - 2}px;
+            color: ${colors.muted};
+            margin-bottom: 10px;
+            font-style: italic;
+          }
+
+          .example,
+          .hint {
+            color: ${colors.example};
+          }
+
+          .dictionary-section {
+            margin-bottom: 20px;
+          }
+
+          .dictionary-header {
+            font-size: ${parseInt(fontSize) - 1}px;
+            font-weight: bold;
+            color: #fff;
+            background: ${colors.accent};
+            border-radius: 3px;
+            padding: 2px 8px;
+            margin-bottom: 10px;
+          }
+
+          .homograph-separator {
+            border-top: 1px dashed ${colors.border};
+            margin: 16px 0;
+          }
+
+          ${cssContent}
+
+          ${this.getRegionCss()}
+
+          img {
+            max-width: 100%;
+            height: auto;
+          }
+
+          table {
+            border-collapse: collapse;
+            max-width: 100%;
+            font-size: ${parseInt(fontSize) - 1}px;
+          }
+
+          a {
+            color: ${colors.link};
+            text-decoration: none;
+          }
+
+          a:hover {
+            text-decoration: underline;
+          }
+        </style>
+      </head>
+      <body>
+        ${body}
+      </body>
+      </html>
+    `;
+  }
+
+  // 组合字体：所选字体 + 可配置的后备字体
+  getFontFamilyStack() {
+    const primary = this.displaySettings.fontFamily || 'Segoe UI';
+    const fallbacks = MdictParser.normalizeFontFallbacks(this.displaySettings.fontFallbacks);
+    return [primary, ...fallbacks]
+      .map(font => MdictParser.GENERIC_FONT_FAMILIES.includes(font) ? font : `'${font}'`)
+      .join(', ');
+  }
+
+  // 显示当前词条的完整内容（只对这一次渲染不受 maxRenderBytes 限制）
+  renderCurrentFull() {
+    if (!this.lastEntry) {
+      return null;
+    }
+    return this.renderEntry(this.lastEntry, true);
+  }
+
+  // 在标签边界处截断 HTML，不切断标签或实体，并补上截断处仍未闭合的标签
+  truncateHtml(html, maxBytes) {
+    let cut = MdictParser.truncateUtf8(html, maxBytes).length;
+    const lastOpen = html.lastIndexOf('<', cut - 1);
+    const lastClose = html.lastIndexOf('>', cut - 1);
+    if (lastOpen > lastClose) {
+      cut = lastOpen;
+    }
+    // 截断处之前的 & 之后只有实体名（没有分号）时，实体被切开
+    const lastAmp = html.lastIndexOf('&', cut - 1);
+    if (lastAmp > -1 && /^&(#x?[0-9a-f]*|[a-z][a-z0-9]*)?$/i.test(html.slice(lastAmp, cut))) {
+      cut = lastAmp;
+    }
+
+    const kept = html.slice(0, cut);
+    const open = [];
+    const tagPattern = /<(\/?)([a-z][a-z0-9-]*)\b[^>]*?(\/?)>/gi;
+    let match;
+    while ((match = tagPattern.exec(kept)) !== null) {
+      const [, closing, name, selfClosing] = match;
+      const tag = name.toLowerCase();
+      if (selfClosing || MdictParser.VOID_ELEMENTS.has(tag)) {
+        continue;
+      }
+      if (!closing) {
+        open.push(tag);
+      } else if (open.lastIndexOf(tag) > -1) {
+        open.length = open.lastIndexOf(tag);
+      }
+    }
+    return kept + open.reverse().map(tag => `</${tag}>`).join('');
+  }
+
+  // 使用最新的显示设置重新渲染当前词条（不重新读取记录）
+  restyleCurrent() {
+    if (!this.lastEntry) {
+      return null;
     }
+    return this.renderEntry(this.lastEntry);
   }
 
   processDefinition(definition, word) {
     // 处理定义内容
-    let html = definition;
+    let html = this.applyStyleSheet(definition);
+
+    // 部分词典把 HTML 整体转义了一次，需要还原（按词典开启）
+    if (this.overrides.unescapeEntities && this.isDoubleEscaped(html)) {
+      html = this.unescapeHtml(html);
+    }
 
     // 处理相对路径的资源链接
     html = html.replace(
@@ -186,7 +762,12 @@ class MdictParser {
         if (!src.startsWith('http') && !src.startsWith('data:') && !src.startsWith('mdd-resource://')) {
           const resourceName = path.basename(src).replace(/\\/g, '/');
           // 标记需要从MDD加载
-          return match.replace(src, `mdd-resource://${resourceName}`);
+          const debugAttrs = this.debugResourceAttrs(src, resourceName);
+          const img = match.replace(src, `mdd-resource://${resourceName}`).replace(/^<img/i, `<img${debugAttrs}`);
+          if (debugAttrs.includes('data-debug="missing"')) {
+            return `${img}<span class="resource-missing">[missing: ${this.escapeHtml(resourceName)}]</span>`;
+          }
+          return img;
         }
         return match;
       }
@@ -198,7 +779,7 @@ class MdictParser {
       (match, before, href, ext, after) => {
         if (!href.startsWith('http') && !href.startsWith('mdd-resource://')) {
           const resourceName = path.basename(href).replace(/\\/g, '/');
-          return `<a${before}href="mdd-resource://${resourceName}"${after} data-audio="true">`;
+          return `<a${before}href="mdd-resource://${resourceName}"${after} data-audio="true"${this.debugResourceAttrs(href, resourceName)}>`;
         }
         return match;
       }
@@ -208,13 +789,36 @@ class MdictParser {
   }
 
   async getResource(resourceName) {
-    if (!this.mdd) {
-      return null;
+    return this.locateResource(resourceName);
+  }
+
+  // 同步定位 MDD 资源，按分卷顺序查找，找不到时返回 null
+  locateResource(resourceName) {
+    for (const mdd of this.mdds) {
+      const data = this.locateResourceIn(mdd, resourceName);
+      if (data) {
+        return data;
+      }
     }
+    return null;
+  }
 
+  locateResourceIn(mdd, resourceName) {
     try {
-      // MDD类使用locate()方法
-      const result = this.mdd.locate(resourceName);
+      // MDD类使用locate()方法；MDD 中的键通常是 "\\img\\cat.png" 形式
+      let result = mdd.locate(resourceName);
+      if (!result || !result.definition) {
+        result = mdd.locate('\\' + this.normalizeResourceKey(resourceName, mdd).replace(/\//g, '\\'));
+      }
+
+      // 分隔符或大小写不同、或一个记录以 "a.png;b.png" 形式保存多个名称时，按索引查找实际的键
+      if (!result || !result.definition) {
+        const aliasKey = this.getResourceAliases(mdd).get(this.normalizeResourceKey(resourceName, mdd));
+        if (aliasKey) {
+          result = mdd.locate(aliasKey);
+        }
+      }
+
       if (result && result.definition) {
         return result.definition;
       }
@@ -225,6 +829,63 @@ class MdictParser {
     return null;
   }
 
+  hasResource(resourceName) {
+    return this.locateResource(resourceName) !== null;
+  }
+
+  // 调试模式下标注资源的解析过程：原始路径、规范化名称、MDD 中是否存在
+  debugResourceAttrs(src, resourceName) {
+    if (!this.displaySettings.debugResources) {
+      return '';
+    }
+    const status = this.hasResource(resourceName) ? 'found' : 'missing';
+    return ` data-debug="${status}" data-debug-src="${this.escapeHtml(src)}" data-debug-name="${this.escapeHtml(resourceName)}"`;
+  }
+
+  // 资源名规范化：统一使用 "/" 分隔、去掉开头的分隔符，mdd（正在查找的分卷）的键不区分大小写时转为小写
+  normalizeResourceKey(name, mdd = this.mdd) {
+    const normalized = name.replace(/\\/g, '/').replace(/^\/+/, '');
+    return this.isKeyCaseSensitive(mdd) ? normalized : normalized.toLowerCase();
+  }
+
+  // 词头是否区分大小写（头部的 KeyCaseSensitive）
+  isKeyCaseSensitive(source = this.mdx) {
+    const header = source && source.header ? source.header : {};
+    return String(header.KeyCaseSensitive || '').toLowerCase() === 'yes';
+  }
+
+  // MDD 键索引：规范化名称 -> 实际的键，包含多名称键拆出的别名（每个分卷首次使用时构建）
+  getResourceAliases(mdd = this.mdd) {
+    if (!this.resourceAliases) {
+      this.resourceAliases = new Map();
+    }
+    if (this.resourceAliases.has(mdd)) {
+      return this.resourceAliases.get(mdd);
+    }
+
+    const aliases = new Map();
+    this.resourceAliases.set(mdd, aliases);
+    this.getKeywordList(mdd).forEach(item => {
+      const key = item.keyText;
+      if (!key) {
+        return;
+      }
+      aliases.set(this.normalizeResourceKey(key, mdd), key);
+      if (!/[;\s]/.test(key.trim())) {
+        return;
+      }
+      const names = key.split(/[;\s]+/).filter(Boolean);
+      // 只有每个部分都像文件名时才拆分，避免误拆带空格的文件名
+      if (names.length > 1 && names.every(name => /\.[a-z0-9]+$/i.test(name))) {
+        names.forEach(name => {
+          aliases.set(this.normalizeResourceKey(name, mdd), key);
+        });
+      }
+    });
+
+    return aliases;
+  }
+
   escapeHtml(text) {
     const map = {
       '&': '&amp;',
@@ -236,23 +897,1225 @@ class MdictParser {
     return text.replace(/[&<>"']/g, m => map[m]);
   }
 
-  getSuggestions(word) {
-    // 简单的建议词生成（可以改进）
+  // 提取纯文本：去掉样式、脚本和标签，并还原常见实体
+  htmlToText(html) {
+    return this.unescapeHtml(
+      html
+        .replace(/<(style|script)[^>]*>[\s\S]*?<\/\1>/gi, ' ')
+        .replace(/<br\s*\/?>|<\/(p|div|li|h\d)>/gi, '\n')
+        .replace(/<[^>]+>/g, ' ')
+        .replace(/&nbsp;/g, ' ')
+        .replace(/&#(x[0-9a-f]+|\d+);/gi, (match, code) => {
+          const point = code[0].toLowerCase() === 'x' ? parseInt(code.slice(1), 16) : parseInt(code, 10);
+          return point > 0 && point <= 0x10FFFF ? String.fromCodePoint(point) : match;
+        })
+    )
+      .replace(/[ \t]+/g, ' ')
+      .replace(/\s*\n\s*/g, '\n')
+      .trim();
+  }
+
+  // 查询词条的纯文本内容（跟随 @@@LINK= 重定向），找不到时返回 null
+  lookupText(word) {
     if (!this.mdx) {
-      return 'try checking your spelling';
+      return null;
     }
 
-    try {
-      // 获取建议词
-      const suggestions = this.mdx.suggest(word, 2);
-      if (suggestions && suggestions.length > 0) {
-        return suggestions.slice(0, 5).map(s => s.keyText).join(', ');
+    let result = this.lookupKey(word);
+    if (result && result.definition) {
+      const targetWord = this.getRedirectTarget(result.definition);
+      if (targetWord) {
+        result = this.lookupKey(targetWord);
       }
-      return 'try checking your spelling';
-    } catch (error) {
-      return 'try checking your spelling';
     }
+
+    if (!result || !result.definition) {
+      return null;
+    }
+    return this.htmlToText(result.definition);
   }
-}
+
+  // 搜索建议中的简短释义：纯文本（跟随重定向），按设置只取第一句，
+  // 超过 briefLength 个字符时截断（按码点计算，不切开代理对）
+  getBrief(word) {
+    const text = this.lookupText(word);
+    if (!text) {
+      return '';
+    }
+
+    let brief = text.replace(/\s+/g, ' ');
+    if (this.searchOptions.briefFirstSentence !== false) {
+      // 西文句号前至少 12 个字符，避免在 "n." 之类的缩写处断句
+      const sentence = brief.match(/^(?:.{12,}?[.!?;](?=\s|$)|.+?[。！？；])/);
+      if (sentence) {
+        brief = sentence[0];
+      }
+    }
+
+    const maxLength = parseInt(this.searchOptions.briefLength) || 80;
+    const chars = Array.from(brief);
+    return chars.length > maxLength ? `${chars.slice(0, maxLength).join('').trimEnd()}…` : brief;
+  }
+
+  // 判断记录是否是被转义过的 HTML：包含 &lt;标签 但没有真正的标签
+  isDoubleEscaped(text) {
+    return /&lt;\/?[a-z][^&]*&gt;/i.test(text) && !/<\/?[a-z][^>]*>/i.test(text);
+  }
+
+  unescapeHtml(text) {
+    const map = {
+      '&lt;': '<',
+      '&gt;': '>',
+      '&quot;': '"',
+      '&#039;': "'",
+      '&#39;': "'",
+      '&amp;': '&'
+    };
+    return text.replace(/&(lt|gt|quot|#0?39|amp);/g, m => map[m]);
+  }
+
+  // 从释义中提取词汇等级（如 CEFR 的 B2、HSK 3），选择器按词典配置
+  extractLevel(word) {
+    if (this.levelCache.has(word)) {
+      return this.levelCache.get(word);
+    }
+
+    let level = null;
+    const result = this.mdx ? this.mdx.lookup(word) : null;
+    if (result && result.definition) {
+      level = this.extractLevelFromHtml(result.definition);
+    }
+
+    this.levelCache.set(word, level);
+    return level;
+  }
+
+  extractLevelFromHtml(html) {
+    const selectors = this.overrides.levelSelectors || MdictParser.DEFAULT_LEVEL_SELECTORS;
+    for (const className of selectors) {
+      const pattern = new RegExp(
+        `<(\\w+)[^>]*class=["'][^"']*\\b${className.replace(/[^\w-]/g, '')}\\b[^"']*["'][^>]*>([\\s\\S]*?)</\\1>`,
+        'i'
+      );
+      const match = html.match(pattern);
+      if (match) {
+        const level = this.htmlToText(match[2]);
+        if (level) {
+          return level;
+        }
+      }
+    }
+    return null;
+  }
+
+  // 等级排序：CEFR A1 < ... < C2，HSK/数字等级按数值，没有等级的排在最后
+  sortByLevel(items) {
+    const rank = (level) => {
+      if (!level) return Infinity;
+      const cefr = level.toUpperCase().match(/\b([ABC])([12])\b/);
+      if (cefr) {
+        return (cefr[1].charCodeAt(0) - 65) * 2 + parseInt(cefr[2]);
+      }
+      const number = level.match(/\d+/);
+      return number ? parseInt(number[0]) : Infinity;
+    };
+
+    return items
+      .map((item, index) => ({ item, index, rank: rank(this.extractLevel(item.keyText)) }))
+      .sort((a, b) => a.rank - b.rank || a.index - b.index)
+      .map(entry => entry.item);
+  }
+
+  // 按词典头部的 StripKey / KeyCaseSensitive 规则规范化文本，记录每一步的变化
+  normalizeKey(text, steps = null) {
+    const header = this.mdx && this.mdx.header ? this.mdx.header : {};
+    let normalized = text.trim();
+
+    if (String(header.StripKey).toLowerCase() === 'yes') {
+      const stripped = normalized.replace(MdictParser.STRIP_KEY_PATTERN, '');
+      if (steps && stripped !== normalized) {
+        steps.push({ step: 'strip-key', result: stripped });
+      }
+      normalized = stripped;
+    }
+
+    if (String(header.KeyCaseSensitive).toLowerCase() !== 'yes') {
+      const folded = normalized.toLowerCase();
+      if (steps && folded !== normalized) {
+        steps.push({ step: 'case-fold', result: folded });
+      }
+      normalized = folded;
+    }
+
+    return normalized;
+  }
+
+  // 预览查询规范化的结果，以及规范化后的词是否存在
+  normalizeQuery(word) {
+    const steps = [];
+    const normalized = this.normalizeKey(word, steps);
+    return {
+      original: word,
+      normalized: normalized,
+      steps: steps,
+      exists: this.getNormalizedKeys().has(normalized)
+    };
+  }
+
+  // 合并同一词头下所有记录的释义，用分隔线隔开；跳过重定向记录，只有一条时原样返回
+  collectHomographs(word, definition) {
+    const items = this.getHomographs().get(word);
+    if (!items) {
+      return definition;
+    }
+
+    const definitions = items
+      .map(item => this.fetchDefinition(item))
+      .filter(result => result && result.definition && !this.getRedirectTarget(result.definition))
+      .map(result => result.definition);
+    return definitions.length > 1 ? definitions.join('<div class="homograph-separator"></div>') : definition;
+  }
+
+  // 有多条记录的词头 -> 这些记录的词条项（按词典中的顺序），首次使用时构建，只保存重复的词头
+  getHomographs() {
+    if (!this.homographs) {
+      const first = new Map();
+      this.homographs = new Map();
+      this.getKeywordList().forEach(item => {
+        if (!first.has(item.keyText)) {
+          first.set(item.keyText, item);
+        } else if (this.homographs.has(item.keyText)) {
+          this.homographs.get(item.keyText).push(item);
+        } else {
+          this.homographs.set(item.keyText, [first.get(item.keyText), item]);
+        }
+      });
+    }
+    return this.homographs;
+  }
+
+  // 按词头查询；没有完全匹配时，按查询选项（忽略大小写 / 忽略重音）折叠后找到对应的词头再查询
+  lookupKey(word) {
+    // 依次尝试原词、去掉首尾空白和标点的形式（"hello," -> hello）及其小写形式；
+    // 原词在前，词头本身带标点（如 etc.）时仍能直接查到
+    const result = this.mdx.lookup(word);
+    if (result && result.definition) {
+      return result;
+    }
+    for (const variant of MdictParser.queryVariants(word, this.searchOptions.queryTrimChars).slice(1)) {
+      const match = this.mdx.lookup(variant);
+      if (match && match.definition) {
+        return match;
+      }
+    }
+    if (!this.searchOptions.caseInsensitiveLookup && !this.searchOptions.ignoreAccents) {
+      return result;
+    }
+
+    const trimmed = MdictParser.trimQuery(word, this.searchOptions.queryTrimChars);
+    const keyText = this.getFoldedKeys().get(MdictParser.foldKey(trimmed, this.searchOptions));
+    return keyText && keyText !== word ? this.mdx.lookup(keyText) : result;
+  }
+
+  // 折叠后的词头 -> 原词头（多个词头折叠后相同时取词典中的第一个），查询选项变化时重建
+  getFoldedKeys() {
+    if (!this.foldedKeys) {
+      this.foldedKeys = new Map();
+      this.getKeywordList().forEach(item => {
+        const folded = MdictParser.foldKey(item.keyText, this.searchOptions);
+        if (!this.foldedKeys.has(folded)) {
+          this.foldedKeys.set(folded, item.keyText);
+        }
+      });
+    }
+    return this.foldedKeys;
+  }
+
+  // 规范化后的词头集合（首次使用时构建）
+  getNormalizedKeys() {
+    if (!this.normalizedKeys) {
+      this.normalizedKeys = new Set(this.getKeywordList().map(item => this.normalizeKey(item.keyText)));
+    }
+    return this.normalizedKeys;
+  }
+
+  // 构建前缀索引：除原词头外，再按所选变换加入一条别名（如去掉冠词）
+  buildPrefixIndex(transformName = this.searchOptions.prefixTransform || 'identity') {
+    const transform = MdictParser.KEY_TRANSFORMS[transformName] || MdictParser.KEY_TRANSFORMS.identity;
+    const index = [];
+
+    this.getKeywordList().forEach(item => {
+      const key = item.keyText.toLowerCase();
+      index.push({ key, keyText: item.keyText });
+
+      const transformed = transform(item.keyText).toLowerCase();
+      if (transformed && transformed !== key) {
+        index.push({ key: transformed, keyText: item.keyText });
+      }
+    });
+
+    index.sort((a, b) => (a.key < b.key ? -1 : a.key > b.key ? 1 : 0));
+    this.prefixIndex = { transform: transformName, entries: index };
+    return index.length;
+  }
+
+  // 估算前缀索引占用的内存（字符串按 UTF-16 计算，每项另加约 64 字节的对象开销）
+  getPrefixIndexBytes() {
+    if (!this.prefixIndex) {
+      return 0;
+    }
+    return this.prefixIndex.entries.reduce((total, entry) => total + (entry.key.length + entry.keyText.length) * 2 + 64, 0);
+  }
+
+  // 在前缀索引中二分查找，返回去重后的词头
+  prefixSearch(prefix, limit = 20) {
+    if (!this.prefixIndex) {
+      this.buildPrefixIndex();
+    }
+
+    const entries = this.prefixIndex.entries;
+    const query = prefix.trim().toLowerCase();
+    const low = this.findPrefixIndexPosition(query);
+
+    // 大小写不同的词头合并为一个，优先保留与输入大小写一致的写法
+    const exactCase = prefix.trim();
+    const variants = new Map();
+    const end = Math.min(entries.length, low + MdictParser.PREFIX_SCAN_LIMIT);
+    for (let i = low; i < end; i++) {
+      if (!entries[i].key.startsWith(query)) {
+        break;
+      }
+      const keyText = entries[i].keyText;
+      const folded = keyText.toLowerCase();
+      const existing = variants.get(folded);
+      if (!existing || (!existing.startsWith(exactCase) && keyText.startsWith(exactCase))) {
+        variants.set(folded, keyText);
+      }
+    }
+
+    return [...variants.values()]
+      .sort(MdictParser.comparePrefixMatches(prefix))
+      .slice(0, limit);
+  }
+
+  // 前缀索引中第一个 key 不小于 query 的位置
+  findPrefixIndexPosition(query) {
+    const entries = this.prefixIndex.entries;
+    let low = 0;
+    let high = entries.length;
+    while (low < high) {
+      const mid = (low + high) >> 1;
+      if (entries[mid].key < query) {
+        low = mid + 1;
+      } else {
+        high = mid;
+      }
+    }
+    return low;
+  }
+
+  // 分页列出以 prefix 开头的词头：按索引顺序（小写后的字典序）排列，翻页时顺序不变。
+  // 大小写不同的词头只保留先出现的一个。返回 { words, hasMore, totalEstimate }，
+  // totalEstimate 为索引中匹配的项数（含大小写变体和别名，是估计的上限）
+  prefixPage(prefix, offset = 0, limit = 50) {
+    if (!this.prefixIndex) {
+      this.buildPrefixIndex();
+    }
+
+    const entries = this.prefixIndex.entries;
+    const query = prefix.trim().toLowerCase();
+    const low = this.findPrefixIndexPosition(query);
+    const high = query ? this.findPrefixIndexPosition(`${query}\uffff`) : entries.length;
+
+    const seen = new Set();
+    const words = [];
+    let hasMore = false;
+    for (let i = low; i < high; i++) {
+      // 跳过变换生成的别名（如去掉冠词），只列出本身以 prefix 开头的词头，保证按词头排序
+      const folded = entries[i].keyText.toLowerCase();
+      if (folded !== entries[i].key || seen.has(folded)) {
+        continue;
+      }
+      seen.add(folded);
+      if (seen.size <= offset) {
+        continue;
+      }
+      if (words.length === limit) {
+        hasMore = true;
+        break;
+      }
+      words.push(entries[i].keyText);
+    }
+
+    return { words, hasMore, totalEstimate: high - low };
+  }
+
+  // 通配符（* 和 ?）或 /正则/ 搜索词头，不区分大小写。
+  // 最多返回 limit 个结果，扫描时间超过 timeBudgetMs 时停止，结果按字母排序
+  patternSearch(pattern, limit = 50, timeBudgetMs = 200) {
+    const regex = MdictParser.patternToRegExp(pattern);
+    if (!regex) {
+      return [];
+    }
+
+    // 通配符前的固定前缀可以用前缀索引缩小扫描范围
+    const literalPrefix = pattern.startsWith('/') ? '' : pattern.split(/[*?]/)[0].toLowerCase();
+    let candidates;
+    if (literalPrefix) {
+      if (!this.prefixIndex) {
+        this.buildPrefixIndex();
+      }
+      candidates = this.prefixIndex.entries;
+    } else {
+      candidates = this.getKeywordList();
+    }
+
+    const startedAt = Date.now();
+    const results = new Set();
+    let i = 0;
+    if (literalPrefix) {
+      let high = candidates.length;
+      while (i < high) {
+        const mid = (i + high) >> 1;
+        if (candidates[mid].key < literalPrefix) {
+          i = mid + 1;
+        } else {
+          high = mid;
+        }
+      }
+    }
+
+    for (; i < candidates.length && results.size < limit; i++) {
+      const keyText = candidates[i].keyText;
+      if (literalPrefix && !candidates[i].key.startsWith(literalPrefix)) {
+        break;
+      }
+      if (regex.test(keyText)) {
+        results.add(keyText);
+      }
+      if (Date.now() - startedAt > timeBudgetMs) {
+        break;
+      }
+    }
+
+    return [...results].sort((a, b) => a.localeCompare(b));
+  }
+
+  // 全文搜索：在释义正文（纯文本）中查找 query，不区分大小写，返回 [{ word, snippet }]。
+  // 需要读取并解压所有记录，大词典（数十万词条）一次扫描可能需要数十秒，
+  // 因此只在用户明确请求时执行；每扫描一批让出事件循环并调用 onProgress(scanned, total)，
+  // 最近的查询结果缓存在内存中，词典重新加载时失效
+  async fulltextSearch(query, limit = 50, onProgress = null) {
+    const needle = query.trim().toLowerCase();
+    if (!needle) {
+      return [];
+    }
+
+    const cacheKey = `${needle}\u0000${limit}`;
+    if (this.fulltextCache.has(cacheKey)) {
+      return this.fulltextCache.get(cacheKey);
+    }
+
+    const total = this.getKeywordList().length;
+    const results = [];
+    let scanned = 0;
+    for (const entry of this.entries()) {
+      scanned++;
+      if (entry.definition && !entry.definition.startsWith('@@@LINK=')) {
+        const text = this.htmlToText(entry.definition).replace(/\s+/g, ' ');
+        const index = text.toLowerCase().indexOf(needle);
+        if (index !== -1) {
+          results.push({ word: entry.word, snippet: MdictParser.makeSnippet(text, index, needle.length) });
+          if (results.length >= limit) {
+            break;
+          }
+        }
+      }
+
+      if (scanned % MdictParser.FULLTEXT_BATCH_SIZE === 0) {
+        if (onProgress) {
+          onProgress(scanned, total);
+        }
+        await new Promise(resolve => setImmediate(resolve));
+      }
+    }
+    if (onProgress) {
+      onProgress(total, total);
+    }
+
+    this.fulltextCache.set(cacheKey, results);
+    if (this.fulltextCache.size > MdictParser.FULLTEXT_CACHE_SIZE) {
+      this.fulltextCache.delete(this.fulltextCache.keys().next().value);
+    }
+    return results;
+  }
+
+  // 模糊搜索：返回编辑距离不超过 maxDistance 的词头，按距离、长度排序。
+  // 先按首字母和长度范围筛选候选，再计算编辑距离
+  fuzzySearch(query, maxDistance = this.getFuzzyDistance(query), limit = 20) {
+    const target = query.trim().toLowerCase();
+    if (!target) {
+      return [];
+    }
+
+    const results = [];
+    const seen = new Set();
+    this.getKeywordList().forEach(item => {
+      const key = item.keyText.toLowerCase();
+      if (key[0] !== target[0] || Math.abs(key.length - target.length) > maxDistance || seen.has(key)) {
+        return;
+      }
+      const distance = MdictParser.editDistance(target, key, maxDistance);
+      if (distance <= maxDistance) {
+        seen.add(key);
+        results.push({ keyText: item.keyText, distance });
+      }
+    });
+
+    results.sort((a, b) => a.distance - b.distance || a.keyText.length - b.keyText.length);
+    return results.slice(0, limit);
+  }
+
+  // 按查询长度计算允许的编辑距离：短词更严格，长词更宽松
+  getFuzzyDistance(word) {
+    const divisor = parseInt(this.searchOptions.fuzzyDistanceDivisor) || 4;
+    const cap = parseInt(this.searchOptions.fuzzyDistanceCap) || 3;
+    const distance = Math.ceil(word.length / divisor);
+    return Math.min(Math.max(distance, 1), cap);
+  }
+
+  getSuggestions(word) {
+    // 简单的建议词生成（可以改进）
+    if (!this.mdx) {
+      return 'try checking your spelling';
+    }
+
+    try {
+      // 获取建议词
+      let suggestions = this.mdx.suggest(word, this.getFuzzyDistance(word));
+      if (suggestions && suggestions.length > 0) {
+        // 按词汇等级排序（按词典开启），低等级的常用词优先
+        if (this.overrides.sortSuggestionsByLevel) {
+          suggestions = this.sortByLevel(suggestions);
+        }
+        return suggestions.slice(0, 5).map(s => s.keyText).join(', ');
+      }
+      return 'try checking your spelling';
+    } catch (error) {
+      return 'try checking your spelling';
+    }
+  }
+}
+
+// 一次用户操作内的查询保护：按词典分别记录已访问的词并限制每部词典的查询次数，
+// 同一个词在不同词典中都能查到
+MdictParser.MAX_CHAINED_LOOKUPS = 8;
+
+MdictParser.createLookupGuard = function(maxLookups = MdictParser.MAX_CHAINED_LOOKUPS) {
+  const visited = new Map();
+  return {
+    visit(word, scope = '') {
+      if (!visited.has(scope)) {
+        visited.set(scope, new Set());
+      }
+      const seen = visited.get(scope);
+      const key = word.trim().toLowerCase();
+      if (seen.has(key) || seen.size >= maxLookups) {
+        return false;
+      }
+      seen.add(key);
+      return true;
+    }
+  };
+};
+
+// 把样式表中的选择器限定在 scope 之内，避免多部词典的样式互相影响。
+// body / html 选择器替换为 scope 本身；@media 等分组规则递归处理，@font-face、@keyframes 保持不变
+MdictParser.scopeCss = function(css, scope) {
+  css = css.replace(/\/\*[\s\S]*?\*\//g, '');
+  let result = '';
+  let i = 0;
+
+  while (i < css.length) {
+    const open = css.indexOf('{', i);
+    if (open === -1) {
+      result += css.slice(i);
+      break;
+    }
+
+    // 找到与之匹配的右括号
+    let depth = 1;
+    let close = open + 1;
+    while (close < css.length && depth > 0) {
+      if (css[close] === '{') depth++;
+      if (css[close] === '}') depth--;
+      close++;
+    }
+
+    // 前一条规则之后的 @import / @charset 等语句原样保留
+    let prelude = css.slice(i, open);
+    const statementEnd = prelude.lastIndexOf(';');
+    if (statementEnd !== -1) {
+      result += prelude.slice(0, statementEnd + 1);
+      prelude = prelude.slice(statementEnd + 1);
+    }
+
+    const selector = prelude.trim();
+    const block = css.slice(open + 1, close - 1);
+    if (/^@(media|supports|document)/i.test(selector)) {
+      result += `${selector} {${MdictParser.scopeCss(block, scope)}}`;
+    } else if (selector.startsWith('@')) {
+      result += `${selector} {${block}}`;
+    } else {
+      const scoped = selector.split(',').map(part => {
+        const trimmed = part.trim();
+        if (/^(html|body)$/i.test(trimmed)) {
+          return scope;
+        }
+        return `${scope} ${trimmed.replace(/^(html|body)\s+/i, '')}`;
+      }).join(', ');
+      result += `${scoped} {${block}}`;
+    }
+    i = close;
+  }
+
+  return result;
+};
+
+// 查询是否为通配符或正则形式
+MdictParser.isPattern = function(query) {
+  return /[*?]/.test(query) || /^\/.+\/[a-z]*$/.test(query);
+};
+
+// 通配符 / 正则搜索模式的最大长度
+MdictParser.MAX_PATTERN_LENGTH = 100;
+
+// 正则中的嵌套量词（如 (a+)+）和反向引用可能导致回溯时间失控，这类正则不予支持
+MdictParser.isUnsafeRegExp = function(source) {
+  if (/\\[1-9]|\\k</.test(source)) {
+    return true;
+  }
+  // groups 记录每层未闭合的分组中是否含有量词
+  const groups = [false];
+  let inClass = false;
+  for (let i = 0; i < source.length; i++) {
+    const char = source[i];
+    if (char === '\\') {
+      i++;
+    } else if (inClass) {
+      inClass = char !== ']';
+    } else if (char === '[') {
+      inClass = true;
+    } else if (char === '(') {
+      groups.push(false);
+    } else if (char === ')' && groups.length > 1) {
+      const quantified = groups.pop();
+      if (quantified && /^([*+]|\{\d*,)/.test(source.slice(i + 1))) {
+        return true;
+      }
+      groups[groups.length - 1] = groups[groups.length - 1] || quantified;
+    } else if ('*+{'.includes(char)) {
+      groups[groups.length - 1] = true;
+    }
+  }
+  return false;
+};
+
+// 把 "colo*r"、"?at" 或 "/^un.*able$/" 转换为正则表达式，无效、过长或不安全时返回 null
+MdictParser.patternToRegExp = function(pattern) {
+  if (pattern.length > MdictParser.MAX_PATTERN_LENGTH) {
+    return null;
+  }
+  const regexMatch = pattern.match(/^\/(.+)\/([a-z]*)$/);
+  try {
+    if (regexMatch) {
+      if (MdictParser.isUnsafeRegExp(regexMatch[1])) {
+        return null;
+      }
+      return new RegExp(regexMatch[1], regexMatch[2].replace(/[gy]/g, '') + (regexMatch[2].includes('i') ? '' : 'i'));
+    }
+    // 连续的 * 合并为一个，避免 .*.* 之类的重复回溯
+    const source = pattern.trim()
+      .replace(/[.+^${}()|[\]\\]/g, '\\$&')
+      .replace(/\*+/g, '.*')
+      .replace(/\?/g, '.');
+    return new RegExp(`^${source}$`, 'i');
+  } catch (error) {
+    return null;
+  }
+};
+
+// 可指定种子的伪随机数生成器（mulberry32），种子为字符串，同一种子得到相同的序列
+MdictParser.seededRandom = function(seed) {
+  let state = 0;
+  for (let i = 0; i < seed.length; i++) {
+    state = Math.imul(state ^ seed.charCodeAt(i), 2654435761);
+  }
+  return () => {
+    state = (state + 0x6D2B79F5) | 0;
+    let t = Math.imul(state ^ (state >>> 15), 1 | state);
+    t = (t + Math.imul(t ^ (t >>> 7), 61 | t)) ^ t;
+    return ((t ^ (t >>> 14)) >>> 0) / 4294967296;
+  };
+};
+
+// 从 mdd-resource:// 地址取出资源名：去掉查询串和片段并解码百分号编码
+// （浏览器会把空格、非 ASCII 字符等编码），无法解码时使用原文
+MdictParser.resourceNameFromUrl = function(url) {
+  const name = url.replace(/^mdd-resource:\/\//, '').replace(/[?#].*$/, '');
+  try {
+    return decodeURIComponent(name);
+  } catch (error) {
+    return name;
+  }
+};
+
+// 没有结束标签的 HTML 元素，截断时不需要补上
+MdictParser.VOID_ELEMENTS = new Set([
+  'area', 'base', 'br', 'col', 'embed', 'hr', 'img', 'input', 'link', 'meta', 'source', 'track', 'wbr'
+]);
+
+// 截取不超过 maxBytes 字节（UTF-8）的前缀，只在字符边界处截断，不会切开多字节字符或代理对
+MdictParser.truncateUtf8 = function(text, maxBytes) {
+  const buffer = Buffer.from(text, 'utf-8');
+  if (buffer.length <= maxBytes) {
+    return text;
+  }
+  let end = Math.max(maxBytes, 0);
+  while (end > 0 && (buffer[end] & 0xC0) === 0x80) {
+    end--;
+  }
+  return buffer.subarray(0, end).toString('utf-8');
+};
+
+// 全文搜索每批扫描的词条数（批之间报告进度并让出事件循环）和缓存的查询数
+MdictParser.FULLTEXT_BATCH_SIZE = 2000;
+MdictParser.FULLTEXT_CACHE_SIZE = 20;
+
+// 截取匹配位置前后的上下文，两端被截断时加省略号
+MdictParser.makeSnippet = function(text, index, length, context = 40) {
+  const start = Math.max(0, index - context);
+  const end = Math.min(text.length, index + length + context);
+  return `${start > 0 ? '…' : ''}${text.slice(start, end).trim()}${end < text.length ? '…' : ''}`;
+};
+
+// 前缀搜索最多检查的索引项数，前缀很短时避免扫描整个词典
+MdictParser.PREFIX_SCAN_LIMIT = 2000;
+
+// 前缀匹配结果的排序：词头短的在前，长度相同时与输入大小写一致的在前，再按字母排序
+MdictParser.comparePrefixMatches = function(prefix) {
+  const exactCase = prefix.trim();
+  return (a, b) => a.length - b.length ||
+    (b.startsWith(exactCase) ? 1 : 0) - (a.startsWith(exactCase) ? 1 : 0) ||
+    a.localeCompare(b);
+};
+
+// 记录块解压失败或解压结果不完整时的典型错误：zlib 报错，或按偏移切片越界。
+// 块的解压和 adler32 校验在 js-mdict 内部完成，这里只能根据错误类型识别
+MdictParser.isCorruptBlockError = function(error) {
+  return error instanceof RangeError ||
+    /^Z_|zlib|inflate|incorrect header check|invalid (block|distance|stored)|unexpected end/i.test(`${error.code || ''} ${error.message || ''}`);
+};
+
+// 编辑距离（Levenshtein），超过 maxDistance 时提前结束并返回 maxDistance + 1
+MdictParser.editDistance = function(a, b, maxDistance = Infinity) {
+  let previous = Array.from({ length: b.length + 1 }, (_, j) => j);
+  for (let i = 1; i <= a.length; i++) {
+    const current = [i];
+    let rowMin = i;
+    for (let j = 1; j <= b.length; j++) {
+      const cost = a[i - 1] === b[j - 1] ? 0 : 1;
+      current[j] = Math.min(previous[j] + 1, current[j - 1] + 1, previous[j - 1] + cost);
+      rowMin = Math.min(rowMin, current[j]);
+    }
+    if (rowMin > maxDistance) {
+      return maxDistance + 1;
+    }
+    previous = current;
+  }
+  return previous[b.length];
+};
+
+MdictParser.adler32 = function(buffer) {
+  let a = 1;
+  let b = 0;
+  for (let i = 0; i < buffer.length; i++) {
+    a = (a + buffer[i]) % 65521;
+    b = (b + a) % 65521;
+  }
+  return ((b << 16) | a) >>> 0;
+};
+
+// 校验词典文件头：4 字节头部长度（大端）+ 头部内容 + 4 字节 adler32 校验和（小端）。
+// 文件损坏或被截断时给出明确的错误，而不是在解析记录时才失败
+MdictParser.verifyHeader = function(filePath) {
+  const name = path.basename(filePath);
+  const fd = fs.openSync(filePath, 'r');
+  try {
+    const fileSize = fs.fstatSync(fd).size;
+    const lengthBytes = Buffer.alloc(4);
+    if (fs.readSync(fd, lengthBytes, 0, 4, 0) < 4) {
+      throw new Error(`"${name}" is too small to be a dictionary file`);
+    }
+
+    const headerLength = lengthBytes.readUInt32BE(0);
+    if (headerLength === 0 || 4 + headerLength + 4 > fileSize) {
+      throw new Error(`"${name}" has an invalid header length (${headerLength} bytes) - file may be truncated`);
+    }
+
+    const header = Buffer.alloc(headerLength + 4);
+    fs.readSync(fd, header, 0, headerLength + 4, 4);
+    const expected = header.readUInt32LE(headerLength);
+    if (MdictParser.adler32(header.subarray(0, headerLength)) !== expected) {
+      throw new Error(`"${name}" header checksum mismatch - file may be corrupt`);
+    }
+
+    // 3.x 格式的块结构和压缩方式不同，js-mdict 无法解析，直接报告而不是返回错误的内容
+    const version = MdictParser.readEngineVersion(header.subarray(0, headerLength));
+    if (version >= 3) {
+      throw new Error(`"${name}" uses MDict format ${version.toFixed(1)}, which is not supported yet`);
+    }
+
+    // Encrypted 的第 1 位表示词头块被加密，需要注册码才能解密，无法读取；
+    // 第 2 位（只加密键块信息）由 js-mdict 自行解密
+    const encrypted = MdictParser.readHeaderAttribute(header.subarray(0, headerLength), 'Encrypted') || '';
+    const encryptionFlags = /^yes$/i.test(encrypted) ? 1 : parseInt(encrypted, 10) || 0;
+    if (encryptionFlags & 1) {
+      throw new Error(`"${name}" is encrypted and requires a registration key, which is not supported`);
+    }
+  } finally {
+    fs.closeSync(fd);
+  }
+};
+
+// 校验 js-mdict 解析出的记录块信息：块数与记录头一致，各块的累计偏移正确，
+// 压缩大小合计与记录头一致且不超出文件末尾，词条的记录偏移都在解压后的总大小之内。
+// js-mdict 只按累加计算偏移，信息不一致时会返回错误或空白的释义而不报错。
+// 库版本不同、缺少相应字段时跳过该项检查
+MdictParser.verifyRecordInfo = function(mdict, filePath, keywordList = []) {
+  const name = path.basename(filePath);
+  const header = mdict.recordHeader;
+  const infoList = mdict.recordInfoList;
+  if (!header || !Array.isArray(infoList)) {
+    return;
+  }
+  const fail = detail => {
+    throw new Error(`"${name}" has inconsistent record block info: ${detail} - file may be corrupt`);
+  };
+
+  if (header.recordBlocksNum !== undefined && infoList.length !== Number(header.recordBlocksNum)) {
+    fail(`the header lists ${Number(header.recordBlocksNum)} blocks but ${infoList.length} were read`);
+  }
+
+  let packed = 0;
+  let unpacked = 0;
+  infoList.forEach((info, index) => {
+    const packSize = Number(info.packSize);
+    const unpackSize = Number(info.unpackSize);
+    if (!(packSize > 0) || !(unpackSize >= 0)) {
+      fail(`block ${index} has invalid sizes (${info.packSize} / ${info.unpackSize})`);
+    }
+    if ((info.packAccumulateOffset !== undefined && Number(info.packAccumulateOffset) !== packed) ||
+        (info.unpackAccumulatorOffset !== undefined && Number(info.unpackAccumulatorOffset) !== unpacked)) {
+      fail(`block ${index} starts at the wrong offset`);
+    }
+    packed += packSize;
+    unpacked += unpackSize;
+  });
+
+  if (header.recordBlockCompSize !== undefined && packed !== Number(header.recordBlockCompSize)) {
+    fail(`compressed block sizes add up to ${packed} bytes but the header lists ${Number(header.recordBlockCompSize)}`);
+  }
+  const dataStart = Number(mdict.recordBlockDataStartOffset);
+  if (dataStart > 0 && dataStart + packed > fs.statSync(filePath).size) {
+    fail('record blocks extend past the end of the file');
+  }
+
+  const lastOffset = keywordList.reduce((max, item) => Math.max(max, Number(item.recordStartOffset) || 0), 0);
+  if (keywordList.length > 0 && lastOffset >= unpacked) {
+    fail(`entry offset ${lastOffset} is beyond the ${unpacked} bytes of decompressed record data`);
+  }
+};
+
+// 检查读出的词条数是否与头部一致。键块解压后不完整时 js-mdict 只能读出部分词条，
+// 这时记录警告（能确定时指出是哪个键块）并继续使用已读出的词条，不让整部词典加载失败
+MdictParser.checkKeyBlocks = function(mdict, filePath, keywordList = []) {
+  const header = mdict.recordHeader || mdict.keyHeader;
+  if (!header || header.entriesNum === undefined || keywordList.length === Number(header.entriesNum)) {
+    return;
+  }
+
+  const name = path.basename(filePath);
+  const expected = Number(header.entriesNum);
+  let location = '';
+  // 按各键块声明的词条数累加，第一个累计数超过已读出数量的就是不完整的键块
+  const blocks = Array.isArray(mdict.keyBlockInfoList) ? mdict.keyBlockInfoList : [];
+  let counted = 0;
+  for (let index = 0; index < blocks.length; index++) {
+    counted += Number(blocks[index].keyBlockEntriesNum) || 0;
+    if (counted > keywordList.length) {
+      location = ` (key block ${index} of ${blocks.length} is incomplete)`;
+      break;
+    }
+  }
+  console.warn(`"${name}" lists ${expected} entries but ${keywordList.length} were read${location}; entries that could not be read will not be found`);
+};
+
+// 词典的全部 MDD 分卷：主 MDD（未指定时为与 MDX 同名的 .mdd）及同目录下的 主文件名.1.mdd、
+// 主文件名.2.mdd……按编号排序，只返回存在的文件
+MdictParser.findMddFiles = function(mdxFile, mddFile) {
+  if (!mdxFile && !mddFile) {
+    return [];
+  }
+  const primary = mddFile || path.join(path.dirname(mdxFile), `${path.basename(mdxFile, path.extname(mdxFile))}.mdd`);
+  const files = fs.existsSync(primary) ? [primary] : [];
+  const directory = path.dirname(primary);
+  const prefix = `${path.basename(primary, path.extname(primary))}.`.toLowerCase();
+
+  let names;
+  try {
+    names = fs.readdirSync(directory);
+  } catch (error) {
+    return files;
+  }
+
+  const volumes = names
+    .map(name => ({ name, match: name.toLowerCase().startsWith(prefix) && name.slice(prefix.length).match(/^(\d+)\.mdd$/i) }))
+    .filter(volume => volume.match)
+    .sort((a, b) => parseInt(a.match[1], 10) - parseInt(b.match[1], 10))
+    .map(volume => path.join(directory, volume.name));
+  return files.concat(volumes);
+};
+
+// 块开头 4 字节（小端）表示的压缩方式
+MdictParser.COMPRESSION_TYPES = { 0: 'none', 1: 'lzo', 2: 'zlib' };
+
+// 读取第一个键块和第一个记录块的压缩方式。offset 为键块区头部的位置（文件头之后），
+// 2.0 及以上版本的数字为 8 字节且键块区头部后有 4 字节校验和，1.x 为 4 字节
+MdictParser.readBlockCompression = function(fd, offset, version) {
+  const wide = version >= 2;
+  const numberSize = wide ? 8 : 4;
+  const fileSize = fs.fstatSync(fd).size;
+  const read = (position, length) => {
+    const buffer = Buffer.alloc(length);
+    if (position + length > fileSize || fs.readSync(fd, buffer, 0, length, position) < length) {
+      throw new Error('the file is truncated');
+    }
+    return buffer;
+  };
+  const numberAt = (buffer, index) => (wide ? Number(buffer.readBigUInt64BE(index * 8)) : buffer.readUInt32BE(index * 4));
+  const typeAt = position => MdictParser.COMPRESSION_TYPES[read(position, 4).readUInt32LE(0)] || 'unknown';
+
+  // 键块区头部：块数、词条数、（2.x：键块信息解压后大小）、键块信息大小、键块总大小
+  const keyHeader = read(offset, numberSize * (wide ? 5 : 4));
+  const keyInfoSize = numberAt(keyHeader, wide ? 3 : 2);
+  const keyBlocksSize = numberAt(keyHeader, wide ? 4 : 3);
+  const keyBlocksStart = offset + keyHeader.length + (wide ? 4 : 0) + keyInfoSize;
+
+  // 记录区头部：块数、词条数、记录块信息大小、记录块总大小，之后是记录块信息和记录块
+  const recordHeaderStart = keyBlocksStart + keyBlocksSize;
+  const recordHeader = read(recordHeaderStart, numberSize * 4);
+  const recordBlocksStart = recordHeaderStart + recordHeader.length + numberAt(recordHeader, 2);
+
+  return { keyBlocks: typeAt(keyBlocksStart), recordBlocks: typeAt(recordBlocksStart) };
+};
+
+// 检查词典文件但不作为当前词典加载（解析结果用完即丢弃），返回报告：
+// { valid, error, file, type, title, version, encoding, encrypted, compression, entryCount }。
+// 无法使用时 valid 为 false，error 为原因；能读到的字段仍然填写
+MdictParser.inspectFile = function(filePath) {
+  const type = path.extname(filePath).slice(1).toLowerCase();
+  const report = {
+    valid: false,
+    error: null,
+    file: path.basename(filePath),
+    type,
+    title: '',
+    version: '',
+    encoding: '',
+    encrypted: false,
+    compression: null,
+    entryCount: 0
+  };
+
+  let fd = null;
+  try {
+    fd = fs.openSync(filePath, 'r');
+    const lengthBytes = Buffer.alloc(4);
+    const headerLength = fs.readSync(fd, lengthBytes, 0, 4, 0) === 4 ? lengthBytes.readUInt32BE(0) : 0;
+    if (headerLength > 0 && 4 + headerLength + 4 <= fs.fstatSync(fd).size) {
+      const header = Buffer.alloc(headerLength);
+      fs.readSync(fd, header, 0, headerLength, 4);
+      const attribute = name => MdictParser.readHeaderAttribute(header, name) || '';
+      report.title = attribute('Title');
+      report.version = attribute('GeneratedByEngineVersion');
+      report.encoding = attribute('Encoding');
+      report.encrypted = /^yes$/i.test(attribute('Encrypted')) || (parseInt(attribute('Encrypted'), 10) || 0) > 0;
+
+      const version = parseFloat(report.version) || 0;
+      if (version < 3) {
+        try {
+          report.compression = MdictParser.readBlockCompression(fd, 4 + headerLength + 4, version);
+        } catch (error) {
+          report.error = `"${report.file}" has an unreadable block layout: ${error.message}`;
+        }
+      }
+    }
+  } catch (error) {
+    report.error = error.message;
+  } finally {
+    if (fd !== null) {
+      fs.closeSync(fd);
+    }
+  }
+
+  try {
+    if (type !== 'mdx' && type !== 'mdd') {
+      throw new Error(`"${report.file}" is not an MDX or MDD file`);
+    }
+    MdictParser.verifyHeader(filePath);
+    if (report.error) {
+      throw new Error(report.error);
+    }
+    const source = type === 'mdd' ? new MDD(filePath) : new MDX(filePath);
+    const list = source.keywordList || source.keyList || [];
+    MdictParser.verifyRecordInfo(source, filePath, list);
+    report.entryCount = list.length;
+    report.valid = true;
+    report.error = null;
+  } catch (error) {
+    report.error = error.message;
+  }
+  return report;
+};
+
+// 从头部 XML 中读取属性值（2.x 为 UTF-16LE，3.x 为 UTF-8），没有该属性时返回 null
+MdictParser.readHeaderAttribute = function(header, attribute) {
+  for (const encoding of ['utf16le', 'utf-8']) {
+    const match = header.toString(encoding).match(new RegExp(`\\b${attribute}="([^"]*)"`));
+    if (match) {
+      return match[1];
+    }
+  }
+  return null;
+};
+
+// 读取 GeneratedByEngineVersion，没有时返回 0
+MdictParser.readEngineVersion = function(header) {
+  return parseFloat(MdictParser.readHeaderAttribute(header, 'GeneratedByEngineVersion')) || 0;
+};
+
+// 比较两个 MDX 词典的词头覆盖情况（对排序后的词头做归并比较）
+MdictParser.coverageDiff = function(mdxFileA, mdxFileB, sampleSize = 20) {
+  const sortedKeys = (file) => {
+    const mdx = new MDX(file);
+    const list = mdx.keywordList || mdx.keyList || [];
+    const keys = Array.from(new Set(list.map(item => item.keyText)));
+    return keys.sort((a, b) => (a < b ? -1 : a > b ? 1 : 0));
+  };
+
+  const keysA = sortedKeys(mdxFileA);
+  const keysB = sortedKeys(mdxFileB);
+  const report = {
+    onlyA: { count: 0, sample: [] },
+    onlyB: { count: 0, sample: [] },
+    shared: { count: 0, sample: [] }
+  };
+
+  const add = (group, key) => {
+    group.count++;
+    if (group.sample.length < sampleSize) {
+      group.sample.push(key);
+    }
+  };
+
+  let i = 0;
+  let j = 0;
+  while (i < keysA.length && j < keysB.length) {
+    if (keysA[i] === keysB[j]) {
+      add(report.shared, keysA[i]);
+      i++;
+      j++;
+    } else if (keysA[i] < keysB[j]) {
+      add(report.onlyA, keysA[i++]);
+    } else {
+      add(report.onlyB, keysB[j++]);
+    }
+  }
+  while (i < keysA.length) add(report.onlyA, keysA[i++]);
+  while (j < keysB.length) add(report.onlyB, keysB[j++]);
+
+  return report;
+};
+
+// 按词比较两段文本，返回 added / removed / unchanged 片段
+MdictParser.diffWords = function(textA, textB, maxTokens = 2000) {
+  const a = textA.split(/\s+/).filter(Boolean).slice(0, maxTokens);
+  const b = textB.split(/\s+/).filter(Boolean).slice(0, maxTokens);
+
+  // 最长公共子序列
+  const lcs = Array.from({ length: a.length + 1 }, () => new Uint16Array(b.length + 1));
+  for (let i = a.length - 1; i >= 0; i--) {
+    for (let j = b.length - 1; j >= 0; j--) {
+      lcs[i][j] = a[i] === b[j] ? lcs[i + 1][j + 1] + 1 : Math.max(lcs[i + 1][j], lcs[i][j + 1]);
+    }
+  }
+
+  const segments = [];
+  const push = (type, token) => {
+    const last = segments[segments.length - 1];
+    if (last && last.type === type) {
+      last.text += ' ' + token;
+    } else {
+      segments.push({ type, text: token });
+    }
+  };
+
+  let i = 0;
+  let j = 0;
+  while (i < a.length && j < b.length) {
+    if (a[i] === b[j]) {
+      push('unchanged', a[i]);
+      i++;
+      j++;
+    } else if (lcs[i + 1][j] >= lcs[i][j + 1]) {
+      push('removed', a[i++]);
+    } else {
+      push('added', b[j++]);
+    }
+  }
+  while (i < a.length) push('removed', a[i++]);
+  while (j < b.length) push('added', b[j++]);
+
+  return segments;
+};
+
+// 按查询选项折叠文本：ignoreAccents 时分解字符并去掉附加符号（café -> cafe），
+// caseInsensitiveLookup 时做大小写折叠（先转大写再转小写，使 ß 与 ss 等价）
+MdictParser.foldKey = function(text, options = {}) {
+  let folded = text.trim();
+  if (options.ignoreAccents) {
+    folded = folded.normalize('NFD').replace(/\p{M}/gu, '').normalize('NFC');
+  }
+  if (options.caseInsensitiveLookup) {
+    folded = folded.toUpperCase().toLowerCase();
+  }
+  return folded;
+};
+
+// 构建前缀索引时可选的词头变换
+MdictParser.KEY_TRANSFORMS = {
+  identity: key => key,
+  stripArticles: key => key.replace(/^(the|a|an)\s+/i, ''),
+  stripPunctuation: key => key.replace(/[^\p{L}\p{N}\s]/gu, '')
+};
+
+// 常见词典中美式 / 英式发音块的选择器
+MdictParser.REGION_SELECTORS = {
+  us: ['.phons_n_am', '.pron-us', '.us', '.amer', '.pron_us'],
+  uk: ['.phons_br', '.pron-uk', '.uk', '.brit', '.pron_uk']
+};
+
+// MDict 的 StripKey 规则会去掉的标点和空白
+MdictParser.STRIP_KEY_PATTERN = /[ _=,.;:!?@%&#~`()\[\]<>{}\/\\$+\-*^'"\t|]/g;
+
+MdictParser.DEFAULT_LEVEL_SELECTORS = ['level', 'cefr', 'hsk'];
+
+// 查询时从首尾去掉的标点（空白总是去掉），可在配置的 queryTrimChars 中修改
+MdictParser.DEFAULT_QUERY_TRIM_CHARS = '.,;:!?"\'()[]{}<>«»“”‘’„…';
+
+// 去掉首尾的空白和 chars 中的字符（按码点处理，字符串中的字符都视为普通字符）
+MdictParser.trimQuery = function(text, chars = MdictParser.DEFAULT_QUERY_TRIM_CHARS) {
+  const strip = new Set(Array.from(typeof chars === 'string' ? chars : MdictParser.DEFAULT_QUERY_TRIM_CHARS));
+  const codePoints = Array.from(text);
+  let start = 0;
+  let end = codePoints.length;
+  while (start < end && (/\s/.test(codePoints[start]) || strip.has(codePoints[start]))) {
+    start++;
+  }
+  while (end > start && (/\s/.test(codePoints[end - 1]) || strip.has(codePoints[end - 1]))) {
+    end--;
+  }
+  return codePoints.slice(start, end).join('');
+};
+
+// 查询时依次尝试的形式：原词、去掉首尾标点、再转小写，去掉重复和空字符串
+MdictParser.queryVariants = function(word, chars = MdictParser.DEFAULT_QUERY_TRIM_CHARS) {
+  const trimmed = MdictParser.trimQuery(word, chars);
+  return Array.from(new Set([word, trimmed, trimmed.toLowerCase()])).filter(Boolean);
+};
+
+// CSS 通用字体族，不能加引号
+MdictParser.GENERIC_FONT_FAMILIES = ['serif', 'sans-serif', 'monospace', 'cursive', 'fantasy', 'system-ui'];
+
+MdictParser.DEFAULT_FONT_FALLBACKS = ['Tahoma', 'Geneva', 'Verdana', 'Microsoft YaHei', 'sans-serif'];
+
+// 页面配色预设：background 背景，foreground 正文，accent 标题和词典分节，link 链接和词头，
+// example 例句和提示文字，muted 次要信息，border 分隔线
+MdictParser.THEMES = {
+  light: {
+    background: '#ffffff',
+    foreground: '#333333',
+    accent: '#2196F3',
+    link: '#1976D2',
+    example: '#666666',
+    muted: '#999999',
+    border: '#dddddd'
+  },
+  dark: {
+    background: '#1e1e1e',
+    foreground: '#e0e0e0',
+    accent: '#42A5F5',
+    link: '#64B5F6',
+    example: '#aaaaaa',
+    muted: '#888888',
+    border: '#444444'
+  }
+};
+
+// 只接受十六进制和 rgb()/hsl() 颜色，颜色值会直接写入样式表
+MdictParser.isThemeColor = function(value) {
+  return typeof value === 'string' && /^(#[0-9a-f]{3,8}|(rgb|hsl)a?\([\d\s.,%]+\))$/i.test(value.trim());
+};
+
+// 校验自定义配色：只保留已知且合法的颜色
+MdictParser.normalizeCustomTheme = function(colors) {
+  const result = {};
+  if (colors && typeof colors === 'object') {
+    Object.keys(MdictParser.THEMES.light).forEach(name => {
+      if (MdictParser.isThemeColor(colors[name])) {
+        result[name] = colors[name].trim();
+      }
+    });
+  }
+  return result;
+};
+
+// 当前使用的配色：custom 使用 customTheme，缺少的颜色取浅色预设；未知的主题名按浅色处理
+MdictParser.resolveTheme = function(theme, customTheme) {
+  if (theme === 'custom') {
+    return { ...MdictParser.THEMES.light, ...MdictParser.normalizeCustomTheme(customTheme) };
+  }
+  return MdictParser.THEMES[theme] || MdictParser.THEMES.light;
+};
+
+// 校验后备字体列表：接受数组或逗号分隔的字符串，去掉非法字符和重复项
+MdictParser.normalizeFontFallbacks = function(fallbacks) {
+  if (typeof fallbacks === 'string') {
+    fallbacks = fallbacks.split(',');
+  }
+  if (!Array.isArray(fallbacks)) {
+    return [...MdictParser.DEFAULT_FONT_FALLBACKS];
+  }
+
+  const result = [];
+  fallbacks.forEach(font => {
+    const name = String(font).trim().replace(/^['"]|['"]$/g, '');
+    if (name && !/[;{}<>'"]/.test(name) && !result.includes(name)) {
+      result.push(name);
+    }
+  });
+  return result;
+};
 
 module.exports = MdictParser;
@@ -0,0 +1,95 @@
+const fs = require('fs');
+const zlib = require('zlib');
+const Store = require('electron-store');
+
+// 在线查询缓存：保存在数据目录的 online-cache.json 中，按提供者和单词索引。
+// compress 为 true 时文件内容为 zlib 压缩后的 base64 文本，读取时按前缀识别，未压缩的旧缓存照常读取
+class OnlineCache {
+  constructor(ttlHours = 168, compress = false) {
+    this.compress = compress;
+    this.store = new Store({
+      name: 'online-cache',
+      serialize: value => OnlineCache.serialize(value, this.compress),
+      deserialize: OnlineCache.deserialize
+    });
+    this.ttlHours = ttlHours;
+  }
+
+  // 词条中可能包含 "."，不能作为 electron-store 的键路径，因此整体保存在 entries 中
+  getEntries() {
+    const entries = this.store.get('entries', {});
+    return entries && typeof entries === 'object' ? entries : {};
+  }
+
+  getKey(provider, word) {
+    return `${provider}:${word.trim().toLowerCase()}`;
+  }
+
+  // 返回缓存项 { word, html, entries, timestamp }，不检查是否过期；旧缓存项没有 entries
+  get(provider, word) {
+    return this.getEntries()[this.getKey(provider, word)] || null;
+  }
+
+  isExpired(entry) {
+    return Date.now() - entry.timestamp > this.ttlHours * 3600 * 1000;
+  }
+
+  // 写入时顺便清理过期的缓存项
+  set(provider, word, entry) {
+    const entries = this.getEntries();
+    Object.keys(entries).forEach(key => {
+      if (this.isExpired(entries[key])) {
+        delete entries[key];
+      }
+    });
+    entries[this.getKey(provider, word)] = { word: entry.word, html: entry.html, entries: entry.entries, timestamp: Date.now() };
+    this.store.set('entries', entries);
+  }
+
+  setTtlHours(ttlHours) {
+    this.ttlHours = ttlHours;
+  }
+
+  clear() {
+    this.store.set('entries', {});
+  }
+
+  // 切换压缩后立即按新格式重写缓存文件
+  setCompress(compress) {
+    this.compress = compress;
+    this.store.set('entries', this.getEntries());
+  }
+
+  // 返回缓存文件的实际大小和解压后的 JSON 大小（字节）
+  diskUsage() {
+    let fileBytes = 0;
+    try {
+      fileBytes = fs.statSync(this.store.path).size;
+    } catch (error) {
+      fileBytes = 0;
+    }
+    return {
+      fileBytes,
+      logicalBytes: Buffer.byteLength(OnlineCache.serialize({ entries: this.getEntries() }, false))
+    };
+  }
+}
+
+OnlineCache.COMPRESSED_PREFIX = 'zlib:';
+
+OnlineCache.serialize = function(value, compress) {
+  const json = JSON.stringify(value, null, '\t');
+  if (!compress) {
+    return json;
+  }
+  return OnlineCache.COMPRESSED_PREFIX + zlib.deflateSync(json).toString('base64');
+};
+
+OnlineCache.deserialize = function(text) {
+  if (text.startsWith(OnlineCache.COMPRESSED_PREFIX)) {
+    text = zlib.inflateSync(Buffer.from(text.slice(OnlineCache.COMPRESSED_PREFIX.length), 'base64')).toString('utf8');
+  }
+  return JSON.parse(text);
+};
+
+module.exports = OnlineCache;
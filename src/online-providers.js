@@ -0,0 +1,291 @@
+const { app, net } = require('electron');
+
+// 所有在线请求共用的设置：超时时间、同时进行的请求数上限和 User-Agent。
+// net.fetch 使用默认会话，连接和 TLS 会话在请求之间复用
+const requestOptions = {
+  timeoutMs: 10000,
+  maxConcurrent: 2
+};
+
+// 正在进行的请求数，以及等待名额的请求（按到达顺序）
+let activeRequests = 0;
+const waitingRequests = [];
+
+function setRequestTimeout(seconds) {
+  requestOptions.timeoutMs = Math.round(seconds * 1000);
+}
+
+// 同时进行的在线请求数上限，超出的请求排队等待
+function setRequestConcurrency(limit) {
+  requestOptions.maxConcurrent = Math.max(1, Math.round(limit) || 1);
+  startWaitingRequests();
+}
+
+function startWaitingRequests() {
+  while (waitingRequests.length > 0 && activeRequests < requestOptions.maxConcurrent) {
+    activeRequests++;
+    waitingRequests.shift()();
+  }
+}
+
+function abortError() {
+  const error = new Error('The request was cancelled');
+  error.name = 'AbortError';
+  return error;
+}
+
+// 等待一个请求名额；排队期间 signal 被取消时离开队列并抛出 AbortError
+function acquireRequestSlot(signal) {
+  if (signal && signal.aborted) {
+    return Promise.reject(abortError());
+  }
+  if (waitingRequests.length === 0 && activeRequests < requestOptions.maxConcurrent) {
+    activeRequests++;
+    return Promise.resolve();
+  }
+
+  return new Promise((resolve, reject) => {
+    const cancel = () => {
+      waitingRequests.splice(waitingRequests.indexOf(start), 1);
+      reject(abortError());
+    };
+    const start = () => {
+      if (signal) {
+        signal.removeEventListener('abort', cancel);
+      }
+      resolve();
+    };
+    waitingRequests.push(start);
+    if (signal) {
+      signal.addEventListener('abort', cancel);
+    }
+  });
+}
+
+function releaseRequestSlot() {
+  activeRequests--;
+  startWaitingRequests();
+}
+
+// 发起在线请求：超过超时时间（默认为设置中的超时时间）或 signal 被取消时中止。
+// 名额从发出请求占用到收到响应头，超时时间从拿到名额开始计算
+async function request(url, signal, timeoutMs = requestOptions.timeoutMs) {
+  await acquireRequestSlot(signal);
+
+  const controller = new AbortController();
+  const abort = () => controller.abort();
+  const timer = setTimeout(abort, timeoutMs);
+  if (signal) {
+    if (signal.aborted) {
+      abort();
+    }
+    signal.addEventListener('abort', abort);
+  }
+
+  try {
+    return await net.fetch(url, {
+      signal: controller.signal,
+      headers: { 'User-Agent': `QuickDict/${app.getVersion()}` }
+    });
+  } catch (error) {
+    if (controller.signal.aborted && !(signal && signal.aborted)) {
+      throw new Error(`Request timed out after ${timeoutMs / 1000}s`);
+    }
+    throw error;
+  } finally {
+    clearTimeout(timer);
+    if (signal) {
+      signal.removeEventListener('abort', abort);
+    }
+    releaseRequestSlot();
+  }
+}
+
+function escapeHtml(text) {
+  return String(text)
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;')
+    .replace(/"/g, '&quot;')
+    .replace(/'/g, '&#039;');
+}
+
+// 在线词典提供者：lookup 返回 { word, html, entries }，没有结果时返回 null；
+// lookupEntries 只返回解析后的数据 { word, entries }，由界面自行渲染；
+// search 返回 [{ word, source, online: true }]。signal（AbortSignal）用于取消进行中的请求
+class OnlineProvider {
+  constructor(name, label) {
+    this.name = name;
+    this.label = label;
+  }
+
+  // 在线缓存中区分结果的名称（同一提供者不同语言的结果分开缓存）
+  get cacheName() {
+    return this.name;
+  }
+
+  async lookupEntries(word, signal) {
+    throw new Error(`${this.label} does not support lookups`);
+  }
+
+  async lookup(word, signal) {
+    const result = await this.lookupEntries(word, signal);
+    if (!result) {
+      return null;
+    }
+    return {
+      word: result.word,
+      html: `<div class="word-title">${escapeHtml(result.word)}</div>${this.render(result.entries)}`,
+      entries: result.entries
+    };
+  }
+
+  render(entries) {
+    return '';
+  }
+
+  async search(query, signal) {
+    const entry = await this.lookupEntries(query, signal);
+    return entry ? [{ word: entry.word, source: this.label, online: true }] : [];
+  }
+
+  // 用于检查服务是否可达的地址，没有接入服务时为 null
+  get probeUrl() {
+    return null;
+  }
+
+  // 检查服务是否可达（收到任何 HTTP 响应即可达），返回 { name, label, reachable, status, error, ms }
+  async probe(timeoutMs) {
+    const result = { name: this.name, label: this.label, reachable: false, status: null, error: null, ms: null };
+    if (!this.probeUrl) {
+      result.error = 'Not configured';
+      return result;
+    }
+
+    const startedAt = Date.now();
+    try {
+      const response = await request(this.probeUrl, null, timeoutMs);
+      result.reachable = true;
+      result.status = response.status;
+    } catch (error) {
+      result.error = error.message;
+    }
+    result.ms = Date.now() - startedAt;
+    return result;
+  }
+}
+
+// Free Dictionary API（dictionaryapi.dev），language 为词条语言，不支持的语言按英文处理
+class FreeDictionaryProvider extends OnlineProvider {
+  constructor(options = {}) {
+    super('freeDictionary', 'Free Dictionary');
+    this.language = FreeDictionaryProvider.LANGUAGES.includes(options.language) ? options.language : 'en';
+  }
+
+  // 英文结果沿用原来的缓存名称，已有的缓存仍然有效
+  get cacheName() {
+    return this.language === 'en' ? this.name : `${this.name}:${this.language}`;
+  }
+
+  async lookupEntries(word, signal) {
+    const url = FreeDictionaryProvider.entryUrl(this.language, word);
+    const response = await request(url, signal);
+    if (response.status === 404) {
+      return null;
+    }
+    if (!response.ok) {
+      throw new Error(`${this.label} returned HTTP ${response.status}`);
+    }
+
+    const entries = FreeDictionaryProvider.parseEntries(await response.json());
+    if (entries.length === 0) {
+      return null;
+    }
+    return { word: entries[0].word || word.trim(), entries };
+  }
+
+  get probeUrl() {
+    return FreeDictionaryProvider.entryUrl(this.language, 'hello');
+  }
+
+  // 每个词性一段，使用 class="pos" 标记，与本地词典的词性过滤兼容
+  render(entries) {
+    return entries.map(entry => {
+      const meanings = entry.meanings.map(meaning => {
+        const definitions = meaning.definitions.map(item => `
+          <li>${escapeHtml(item.definition)}${item.example ? `<div class="example">${escapeHtml(item.example)}</div>` : ''}</li>
+        `).join('');
+        return `<div class="pos">${escapeHtml(meaning.partOfSpeech)}</div><ol>${definitions}</ol>`;
+      }).join('');
+
+      return `<div class="online-entry">
+        ${entry.phonetic ? `<div class="phonetic">${escapeHtml(entry.phonetic)}</div>` : ''}
+        ${meanings}
+      </div>`;
+    }).join('');
+  }
+}
+
+// 翻译类提供者的占位实现，尚未接入具体服务
+class TranslationProvider extends OnlineProvider {
+  constructor() {
+    super('translation', 'Translation');
+  }
+
+  async lookupEntries(word, signal) {
+    throw new Error(`${this.label} provider is not configured yet`);
+  }
+}
+
+// dictionaryapi.dev 支持的语言
+FreeDictionaryProvider.LANGUAGES = ['en', 'es', 'fr', 'de', 'it', 'pt-BR', 'ru', 'ja', 'ko', 'hi', 'ar', 'tr'];
+
+FreeDictionaryProvider.entryUrl = function(language, word) {
+  return `https://api.dictionaryapi.dev/api/v2/entries/${encodeURIComponent(language)}/${encodeURIComponent(word.trim())}`;
+};
+
+// 把 API 返回的数据整理为 [{ word, phonetic, meanings: [{ partOfSpeech, definitions: [{ definition, example }] }] }]，
+// 缺少的字段用空字符串补齐，格式不对时返回空数组
+FreeDictionaryProvider.parseEntries = function(data) {
+  if (!Array.isArray(data)) {
+    return [];
+  }
+  return data.filter(entry => entry && typeof entry === 'object').map(entry => ({
+    word: String(entry.word || ''),
+    phonetic: String(entry.phonetic || (entry.phonetics || []).map(p => p && p.text).find(Boolean) || ''),
+    meanings: (entry.meanings || []).map(meaning => ({
+      partOfSpeech: String(meaning.partOfSpeech || ''),
+      definitions: (meaning.definitions || []).map(item => ({
+        definition: String(item.definition || ''),
+        example: String(item.example || '')
+      }))
+    }))
+  }));
+};
+
+const PROVIDERS = {
+  freeDictionary: FreeDictionaryProvider,
+  translation: TranslationProvider
+};
+
+// 按名称创建提供者，未知名称时使用 Free Dictionary；options.language 为在线词典的语言
+function createProvider(name, options = {}) {
+  const Provider = PROVIDERS[name] || FreeDictionaryProvider;
+  return new Provider(options);
+}
+
+// 并行检查所有提供者是否可达，每个最多等待 timeoutMs（不使用设置中的超时时间）
+function probeProviders(options = {}, timeoutMs = 3000) {
+  return Promise.all(Object.keys(PROVIDERS).map(name => createProvider(name, options).probe(timeoutMs)));
+}
+
+module.exports = {
+  escapeHtml,
+  OnlineProvider,
+  FreeDictionaryProvider,
+  PROVIDERS,
+  createProvider,
+  probeProviders,
+  setRequestConcurrency,
+  setRequestTimeout
+};
@@ -0,0 +1,147 @@
+const fs = require('fs');
+const path = require('path');
+
+const HAN_PATTERN = /\p{Script=Han}/u;
+const INDEX_VERSION = 1;
+
+// 每处理这么多个词头让出一次事件循环，建立大词典的索引时不阻塞界面
+const BUILD_BATCH_SIZE = 2000;
+
+// 汉字词头的拼音索引：entries 为 [无声调拼音, 带声调数字的读音, 词头]，按无声调拼音排序，
+// 例如 ['zhongwen', 'zhong1 wen2', '中文']
+class PinyinIndex {
+  constructor(entries = []) {
+    this.entries = entries;
+  }
+
+  get size() {
+    return this.entries.length;
+  }
+
+  // 按拼音前缀搜索，返回 [{ word, reading }]。输入带声调数字时（zhong1wen）只匹配对应声调，
+  // 没有写声调的音节匹配任意声调；完全匹配的排在前面
+  search(query, limit = 20) {
+    const typed = PinyinIndex.normalizeQuery(query);
+    const toneless = typed.replace(/\d/g, '');
+    if (!toneless) {
+      return [];
+    }
+    const tonePattern = /\d/.test(typed) ? PinyinIndex.tonePattern(typed) : null;
+
+    const matches = [];
+    for (let i = this.lowerBound(toneless); i < this.entries.length; i++) {
+      const [key, reading, word] = this.entries[i];
+      if (!key.startsWith(toneless)) {
+        break;
+      }
+      if (!tonePattern || tonePattern.test(reading.replace(/ /g, ''))) {
+        matches.push({ word, reading, exact: key === toneless });
+      }
+    }
+
+    return matches
+      .sort((a, b) => (b.exact - a.exact) || a.word.length - b.word.length)
+      .slice(0, limit)
+      .map(({ word, reading }) => ({ word, reading }));
+  }
+
+  // 第一个不小于 key 的位置
+  lowerBound(key) {
+    let low = 0;
+    let high = this.entries.length;
+    while (low < high) {
+      const middle = (low + high) >> 1;
+      if (this.entries[middle][0] < key) {
+        low = middle + 1;
+      } else {
+        high = middle;
+      }
+    }
+    return low;
+  }
+
+  async save(file, signature) {
+    await fs.promises.mkdir(path.dirname(file), { recursive: true });
+    const temporary = `${file}.partial`;
+    await fs.promises.writeFile(temporary, JSON.stringify({ version: INDEX_VERSION, signature, entries: this.entries }));
+    await fs.promises.rename(temporary, file);
+  }
+}
+
+// 从磁盘读取索引；文件不存在、版本不符或词典已变化（签名不同）时返回 null
+PinyinIndex.load = async function(file, signature) {
+  try {
+    const data = JSON.parse(await fs.promises.readFile(file, 'utf-8'));
+    if (data.version !== INDEX_VERSION || data.signature !== signature || !Array.isArray(data.entries)) {
+      return null;
+    }
+    return new PinyinIndex(data.entries);
+  } catch (error) {
+    return null;
+  }
+};
+
+// 为词头列表中含汉字的词头建立索引，非汉字部分忽略
+PinyinIndex.build = async function(words) {
+  // pinyin-pro 的字典较大，只在建立索引时加载
+  const { pinyin } = require('pinyin-pro');
+  const entries = [];
+
+  for (let start = 0; start < words.length; start += BUILD_BATCH_SIZE) {
+    words.slice(start, start + BUILD_BATCH_SIZE).forEach(word => {
+      if (!HAN_PATTERN.test(word)) {
+        return;
+      }
+      const syllables = pinyin(word, { toneType: 'num', type: 'array', nonZh: 'removed', v: true })
+        .map(syllable => syllable.toLowerCase().replace(/[^a-z0-9]/g, ''))
+        .filter(Boolean);
+      if (syllables.length > 0) {
+        const reading = syllables.join(' ');
+        entries.push([reading.replace(/[\d ]/g, ''), reading, word]);
+      }
+    });
+    await new Promise(resolve => setImmediate(resolve));
+  }
+
+  entries.sort((a, b) => (a[0] < b[0] ? -1 : a[0] > b[0] ? 1 : 0));
+  return new PinyinIndex(entries);
+};
+
+// 词典文件的签名（大小和修改时间），文件变化后需要重建索引
+PinyinIndex.signatureOf = function(file) {
+  const stat = fs.statSync(file);
+  return `${stat.size}:${Math.floor(stat.mtimeMs)}`;
+};
+
+// 规范化输入：小写，去掉空格和隔音符号，ü 写作 v，轻声的 5 按 0 处理
+PinyinIndex.normalizeQuery = function(query) {
+  return query.trim().toLowerCase()
+    .replace(/[üǖǘǚǜ]/g, 'v')
+    .replace(/u:/g, 'v')
+    .replace(/5/g, '0')
+    .replace(/[\s']/g, '');
+};
+
+// 把带声调数字的输入转成匹配读音前缀的正则：字母后没有写声调时允许任意声调
+PinyinIndex.tonePattern = function(typed) {
+  let source = '^';
+  for (let i = 0; i < typed.length; i++) {
+    const char = typed[i];
+    if (/\d/.test(char)) {
+      source += char;
+    } else {
+      source += char.replace(/[^a-z]/g, '\\$&');
+      if (!/\d/.test(typed[i + 1] || '')) {
+        source += '\\d?';
+      }
+    }
+  }
+  return new RegExp(source);
+};
+
+// 输入看起来像拼音（只有字母、声调数字、空格和隔音符号）时才查拼音索引
+PinyinIndex.isPinyinQuery = function(query) {
+  return /^[a-zü:'\s0-5]+$/i.test(query.trim()) && /[a-z]/i.test(query);
+};
+
+module.exports = PinyinIndex;
@@ -0,0 +1,98 @@
+// 配置方案：一组词典路径和显示设置。当前方案的设置就是配置中的普通字段，
+// 其他方案的设置保存在 profiles 中，切换时互相交换
+const PROFILE_FIELDS = [
+  'dictionaries',
+  'mdxFile',
+  'mddFile',
+  'cssFile',
+  'fontFamily',
+  'fontSize',
+  'lineHeight',
+  'fontFallbacks',
+  'showSourceBadge',
+  'maxRenderBytes',
+  'preferredRegion',
+  'showRedirectBanner',
+  'redirectBannerText',
+  'theme',
+  'customTheme'
+];
+
+const DEFAULT_PROFILE = 'default';
+
+// 读取配置中属于方案的字段（只包含已设置的字段）
+function snapshotProfile(store) {
+  const profile = {};
+  PROFILE_FIELDS.forEach(field => {
+    if (store.has(field)) {
+      profile[field] = store.get(field);
+    }
+  });
+  return profile;
+}
+
+class Profiles {
+  constructor(store) {
+    this.store = store;
+  }
+
+  get active() {
+    return this.store.get('activeProfile', DEFAULT_PROFILE);
+  }
+
+  // profiles 整体读写，方案名可能含有点号，不能作为 electron-store 的键路径
+  getAll() {
+    const profiles = this.store.get('profiles', {});
+    if (!profiles[this.active]) {
+      profiles[this.active] = snapshotProfile(this.store);
+    }
+    return profiles;
+  }
+
+  list() {
+    return Object.keys(this.getAll()).map(name => ({ name, active: name === this.active }));
+  }
+
+  // 以当前设置为基础新建方案，不切换
+  create(name) {
+    const profiles = this.getAll();
+    if (profiles[name]) {
+      throw new Error(`Profile "${name}" already exists`);
+    }
+    profiles[name] = snapshotProfile(this.store);
+    this.store.set('profiles', profiles);
+  }
+
+  // 保存当前方案，再把目标方案的字段写入配置（目标方案没有的字段恢复默认值）
+  switch(name) {
+    const profiles = this.getAll();
+    if (!profiles[name]) {
+      throw new Error(`Profile "${name}" does not exist`);
+    }
+
+    profiles[this.active] = snapshotProfile(this.store);
+    PROFILE_FIELDS.forEach(field => {
+      if (profiles[name][field] !== undefined) {
+        this.store.set(field, profiles[name][field]);
+      } else {
+        this.store.delete(field);
+      }
+    });
+    this.store.set('profiles', profiles);
+    this.store.set('activeProfile', name);
+  }
+
+  delete(name) {
+    if (name === this.active) {
+      throw new Error('The active profile cannot be deleted');
+    }
+    const profiles = this.getAll();
+    delete profiles[name];
+    this.store.set('profiles', profiles);
+  }
+}
+
+Profiles.DEFAULT_PROFILE = DEFAULT_PROFILE;
+Profiles.snapshot = snapshotProfile;
+
+module.exports = Profiles;
@@ -0,0 +1,49 @@
+// 在本地释义和在线释义之间切换：记住当前词两种来源的渲染结果，来回切换时不必重新查询。
+// renderers 为 { local, online }，均为 async word => html，没有结果时返回 null
+class SourceToggle {
+  constructor(renderers) {
+    this.renderers = renderers;
+    this.word = null;
+    this.renderings = new Map();
+  }
+
+  clear() {
+    this.word = null;
+    this.renderings.clear();
+  }
+
+  // 记录已经显示过的渲染结果；换了一个词时丢弃上一个词的结果
+  remember(word, source, html) {
+    const key = word.trim().toLowerCase();
+    if (key !== this.word) {
+      this.clear();
+      this.word = key;
+    }
+    this.renderings.set(source, html);
+  }
+
+  async render(word, source) {
+    if (word.trim().toLowerCase() !== this.word || !this.renderings.has(source)) {
+      this.remember(word, source, await this.renderers[source](word.trim()));
+    }
+    return this.renderings.get(source);
+  }
+
+  // 返回另一种来源的渲染结果 { source, html, missing }；另一种来源没有结果（或请求失败）时
+  // 留在当前来源，missing 为没有结果的来源
+  async toggle(word, current) {
+    const target = current === 'online' ? 'local' : 'online';
+    let html = null;
+    try {
+      html = await this.render(word, target);
+    } catch (error) {
+      console.warn(`Failed to load the ${target} definition:`, error.message);
+    }
+    if (html) {
+      return { source: target, html, missing: null };
+    }
+    return { source: current, html: await this.render(word, current), missing: target };
+  }
+}
+
+module.exports = SourceToggle;
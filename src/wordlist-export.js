@@ -0,0 +1,91 @@
+const fs = require('fs');
+const { once } = require('events');
+
+const FORMATS = ['txt', 'csv', 'json'];
+
+// 每处理这么多个词头报告一次进度并让出事件循环
+const PROGRESS_INTERVAL = 1000;
+
+// CSV 字段：含逗号、引号或换行时用引号包围，内部引号写两次
+function csvField(value) {
+  return /[",\r\n]/.test(value) ? `"${value.replace(/"/g, '""')}"` : value;
+}
+
+// 一个词头在各格式中的写法；txt 每行一个词头，带简短释义时用制表符分隔
+function formatRow(format, row, includeBriefs, first) {
+  if (format === 'json') {
+    return `${first ? '' : ',\n'}  ${JSON.stringify(row)}`;
+  }
+  if (format === 'csv') {
+    const fields = [row.word, row.dictionary].concat(includeBriefs ? [row.brief] : []);
+    return `${fields.map(csvField).join(',')}\r\n`;
+  }
+  const line = includeBriefs && row.brief ? `${row.word}\t${row.brief}` : row.word;
+  return `${line.replace(/[\r\n]+/g, ' ')}\n`;
+}
+
+// 把所有词典的全部词头逐条写入文件（不在内存中拼接整个文件），返回写入的词头数。
+// includeBriefs 时附带简短释义（需要读取每条记录，大词典较慢）；
+// onProgress(written, total) 定期报告进度
+async function exportWordlist(dictionaries, format, filePath, options = {}) {
+  if (!FORMATS.includes(format)) {
+    throw new Error(`Unsupported wordlist format "${format}"`);
+  }
+  const { includeBriefs = false, onProgress = null } = options;
+  const total = dictionaries.reduce((sum, dictionary) => sum + dictionary.getKeywordList().length, 0);
+
+  const stream = fs.createWriteStream(filePath, { encoding: 'utf-8' });
+  const write = async text => {
+    if (!stream.write(text)) {
+      await once(stream, 'drain');
+    }
+  };
+
+  let written = 0;
+  try {
+    if (format === 'json') {
+      await write('[\n');
+    } else if (format === 'csv') {
+      // 带 BOM，Excel 才会按 UTF-8 打开
+      await write(`\uFEFF${['word', 'dictionary'].concat(includeBriefs ? ['brief'] : []).join(',')}\r\n`);
+    }
+
+    for (const dictionary of dictionaries) {
+      const name = dictionary.getDictionaryName();
+      for (const item of dictionary.getKeywordList()) {
+        const row = { word: item.keyText, dictionary: name };
+        if (includeBriefs) {
+          row.brief = dictionary.getBrief(item.keyText);
+        }
+        await write(formatRow(format, row, includeBriefs, written === 0));
+        written++;
+
+        if (written % PROGRESS_INTERVAL === 0) {
+          if (onProgress) {
+            onProgress(written, total);
+          }
+          await new Promise(resolve => setImmediate(resolve));
+        }
+      }
+    }
+
+    if (format === 'json') {
+      await write(written > 0 ? '\n]\n' : ']\n');
+    }
+    stream.end();
+    await once(stream, 'finish');
+  } catch (error) {
+    stream.destroy();
+    throw error;
+  }
+
+  if (onProgress) {
+    onProgress(written, total);
+  }
+  return written;
+}
+
+module.exports = {
+  FORMATS,
+  exportWordlist
+};
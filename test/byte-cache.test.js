@@ -0,0 +1,66 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const ByteLruCache = require('../src/byte-cache');
+
+test('evicts least recently used entries to stay within the byte budget', () => {
+  const cache = new ByteLruCache(10);
+  cache.set('a', 'aaaa');
+  cache.set('b', 'bbbb');
+  cache.get('a');
+  cache.set('c', 'cccc');
+
+  assert.deepStrictEqual([...cache.entries.keys()], ['a', 'c']);
+  assert.strictEqual(cache.bytes, 8);
+  assert.strictEqual(cache.get('b'), undefined);
+});
+
+test('counts bytes rather than characters', () => {
+  const cache = new ByteLruCache(6);
+  cache.set('zh', '中文');
+  assert.strictEqual(cache.bytes, 6);
+  cache.set('buffer', Buffer.alloc(3));
+  assert.deepStrictEqual([...cache.entries.keys()], ['buffer']);
+  assert.strictEqual(cache.bytes, 3);
+});
+
+test('does not cache a value larger than the budget', () => {
+  const cache = new ByteLruCache(4);
+  cache.set('a', 'aa');
+  assert.strictEqual(cache.set('big', 'bbbbb'), false);
+  assert.strictEqual(cache.has('big'), false);
+  assert.strictEqual(cache.get('a'), 'aa');
+});
+
+test('replacing a key updates the byte count', () => {
+  const cache = new ByteLruCache(10);
+  cache.set('a', 'aaaa');
+  cache.set('a', 'aa');
+  assert.strictEqual(cache.size, 1);
+  assert.strictEqual(cache.bytes, 2);
+});
+
+test('shrinking the budget evicts immediately', () => {
+  const cache = new ByteLruCache(12);
+  ['a', 'b', 'c'].forEach(key => cache.set(key, key.repeat(4)));
+  cache.setMaxBytes(5);
+  assert.deepStrictEqual([...cache.entries.keys()], ['c']);
+  cache.setMaxBytes(0);
+  assert.strictEqual(cache.size, 0);
+  assert.strictEqual(cache.bytes, 0);
+});
+
+test('uses a custom size function', () => {
+  const cache = new ByteLruCache(3, value => value.cost);
+  cache.set('a', { cost: 2 });
+  cache.set('b', { cost: 2 });
+  assert.deepStrictEqual([...cache.entries.keys()], ['b']);
+});
+
+test('counts hits and misses', () => {
+  const cache = new ByteLruCache(10);
+  cache.set('a', 'aa');
+  cache.get('a');
+  cache.get('a');
+  cache.get('b');
+  assert.deepStrictEqual({ hits: cache.hits, misses: cache.misses }, { hits: 2, misses: 1 });
+});
@@ -0,0 +1,62 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { classifyClipboardText, DEFAULT_OPTIONS } = require('../src/clipboard-filter');
+
+// [文字, 拒绝原因]，原因为 null 表示接受
+const CASES = [
+  ['hello', null],
+  ['look up', null],
+  ["don't", null],
+  ['well-known', null],
+  ['e.g.', null],
+  ['café', null],
+  ['中文词典', null],
+  ['日本語', null],
+  ['', 'empty'],
+  ['https://example.com/word', 'url'],
+  ['http://localhost:8080', 'url'],
+  ['www.example.com', 'url'],
+  ['mailto:someone', 'url'],
+  ['someone@example.com', 'url'],
+  ['C:\\Users\\me\\dict.mdx', 'path'],
+  ['D:/dictionaries', 'path'],
+  ['/usr/share/dict', 'path'],
+  ['~/notes', 'path'],
+  ['./build', 'path'],
+  ['\\\\server\\share', 'path'],
+  ['12345', 'not-letters'],
+  ['3.14', 'not-letters'],
+  ['2024-01-01', 'not-letters'],
+  ['mp3', 'not-letters'],
+  ['foo()', 'symbols'],
+  ['x = 1', 'symbols'],
+  ['a => b', 'symbols'],
+  ['snake_case', 'symbols'],
+  ['{ key }', 'symbols'],
+  ['$HOME', 'symbols'],
+  ['one two three four', 'too-many-words'],
+  ['a'.repeat(DEFAULT_OPTIONS.maxLength + 1), 'too-long']
+];
+
+test('classifies clipboard text with the default limits', () => {
+  CASES.forEach(([text, reason]) => {
+    assert.deepStrictEqual(classifyClipboardText(text), { accept: reason === null, reason }, JSON.stringify(text));
+  });
+});
+
+test('applies the configured word and length limits', () => {
+  assert.strictEqual(classifyClipboardText('one two', { maxWords: 1 }).reason, 'too-many-words');
+  assert.strictEqual(classifyClipboardText('one two three four', { maxWords: 4 }).accept, true);
+  assert.strictEqual(classifyClipboardText('dictionary', { maxLength: 5 }).reason, 'too-long');
+  assert.strictEqual(classifyClipboardText('a'.repeat(60), { maxLength: 60 }).accept, true);
+});
+
+test('applies the configured letter ratio', () => {
+  assert.strictEqual(classifyClipboardText('mp3', { minLetterRatio: 0.5 }).accept, true);
+  assert.strictEqual(classifyClipboardText('Win32', { minLetterRatio: 0.8 }).reason, 'not-letters');
+});
+
+test('rejects a repeat of the last query regardless of case', () => {
+  assert.strictEqual(classifyClipboardText('Hello', {}, 'hello').reason, 'repeated');
+  assert.strictEqual(classifyClipboardText('hello', {}, 'world').accept, true);
+});
@@ -0,0 +1,102 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const { MemoryStore, tempDir } = require('./helpers');
+const {
+  CONFIG_SCHEMA_VERSION, canonicalizeHotkey, hotkeyToAccelerator, migrateConfig, normalizeConfig, normalizeField
+} = require('../src/config');
+
+// [写法, 规范写法]，规范写法为 null 表示不支持
+const HOTKEYS = [
+  ['shift + ctrl + d', 'Ctrl+Shift+D'],
+  ['Alt+M', 'Alt+M'],
+  ['control+option+KeyD', 'Ctrl+Alt+D'],
+  ['cmd+shift+Digit1', 'Shift+Cmd+1'],
+  ['Ctrl+Numpad1', 'Ctrl+num1'],
+  ['ctrl+numpadadd', 'Ctrl+numadd'],
+  ['alt+f12', 'Alt+F12'],
+  ['ctrl+ArrowLeft', 'Ctrl+Left'],
+  ['meta+Backquote', 'Cmd+`'],
+  ['ctrl+esc', 'Ctrl+Escape'],
+  ['ctrl+shift', null],
+  ['ctrl+f25', null],
+  ['ctrl+KeyDD', null],
+  ['', null],
+  [42, null]
+];
+
+test('canonicalizes hotkey spellings', () => {
+  HOTKEYS.forEach(([hotkey, canonical]) => {
+    assert.strictEqual(canonicalizeHotkey(hotkey), canonical, JSON.stringify(hotkey));
+  });
+});
+
+test('maps Ctrl and Cmd to CommandOrControl', () => {
+  assert.strictEqual(hotkeyToAccelerator('shift + ctrl + d'), 'CommandOrControl+Shift+D');
+  assert.strictEqual(hotkeyToAccelerator('cmd+alt+Numpad1'), 'Alt+CommandOrControl+num1');
+  assert.strictEqual(hotkeyToAccelerator('ctrl+'), null);
+});
+
+test('normalizes an out-of-range font size and a non-canonical hotkey', () => {
+  const store = new MemoryStore();
+  store.set('fontSize', '99');
+  store.set('hotkey', 'shift + ctrl + d');
+  store.set('lineHeight', '1.6');
+
+  assert.deepStrictEqual(normalizeConfig(store), [
+    { field: 'hotkey', from: 'shift + ctrl + d', to: 'Ctrl+Shift+D' },
+    { field: 'fontSize', from: '99', to: '32' }
+  ]);
+  assert.strictEqual(store.get('fontSize'), '32');
+  assert.strictEqual(store.get('hotkey'), 'Ctrl+Shift+D');
+  assert.deepStrictEqual(normalizeConfig(store), []);
+});
+
+test('normalizeConfig fixes booleans, objects and the dictionary list', () => {
+  const store = new MemoryStore();
+  store.set('offlineMode', 'true');
+  store.set('windowBounds', []);
+  store.set('dictionaries', [{ mdxFile: ' a.mdx ', cssFile: 3 }, { mddFile: 'b.mdd' }]);
+
+  normalizeConfig(store);
+  assert.strictEqual(store.get('offlineMode'), true);
+  assert.deepStrictEqual(store.get('windowBounds'), {});
+  assert.deepStrictEqual(store.get('dictionaries'), [{ mdxFile: 'a.mdx' }]);
+});
+
+// [配置项, 设置界面传来的值, 保存的值]
+const FIELDS = [
+  ['onlineCacheTtlHours', '0', 0],
+  ['onlineCacheTtlHours', 'soon', 168],
+  ['onlineTimeoutSeconds', '500', 120],
+  ['onlineMaxConcurrent', '3', 3],
+  ['historySize', '-5', 1],
+  ['clipboardMaxWords', 'x', 3],
+  ['theme', 'neon', 'light'],
+  ['preferredRegion', 'uk', 'uk'],
+  ['hotkeyTarget', 'elsewhere', 'lookup'],
+  ['onlineLanguage', 'xx', 'en'],
+  ['offlineMode', 'true', true],
+  ['autoSpeak', false, false],
+  ['unknownField', 'kept', 'kept']
+];
+
+test('normalizeField applies the same rules as normalizeConfig', () => {
+  FIELDS.forEach(([field, value, expected]) => {
+    assert.strictEqual(normalizeField(field, value), expected, `${field}: ${JSON.stringify(value)}`);
+  });
+});
+
+test('migrates a config without a schema version and backs up the file first', () => {
+  const store = new MemoryStore({ cwd: tempDir() });
+  store.set('mdxFile', 'old.mdx');
+  store.set('fontSize', '16');
+  fs.writeFileSync(store.path, JSON.stringify(store.data));
+
+  assert.deepStrictEqual(migrateConfig(store), { from: 0, to: CONFIG_SCHEMA_VERSION, backup: `${store.path}.v0.bak` });
+  assert.strictEqual(store.get('schemaVersion'), 2);
+  assert.strictEqual(store.get('activeProfile'), 'default');
+  assert.deepStrictEqual(store.get('profiles').default, { mdxFile: 'old.mdx', fontSize: '16' });
+  assert.deepStrictEqual(JSON.parse(fs.readFileSync(`${store.path}.v0.bak`, 'utf8')), { mdxFile: 'old.mdx', fontSize: '16' });
+  assert.strictEqual(migrateConfig(store), null);
+});
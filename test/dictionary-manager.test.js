@@ -0,0 +1,130 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const { writeDictionary, tempDir } = require('./helpers');
+const DictionaryManager = require('../src/dictionary-manager');
+const MdictParser = require('../src/mdict-parser');
+
+// 只提供测试用到的方法的词典替身
+function fakeDictionary(name) {
+  return { getDictionaryName: () => name };
+}
+
+function managerWith(...names) {
+  const manager = new DictionaryManager([]);
+  manager.dictionaries = names.map(fakeDictionary);
+  return manager;
+}
+
+test('cycling from the last dictionary returns to the first', () => {
+  const manager = managerWith('Oxford', 'Collins', 'Longman');
+  assert.strictEqual(manager.cycleActive().getDictionaryName(), 'Collins');
+  assert.strictEqual(manager.cycleActive().getDictionaryName(), 'Longman');
+  assert.strictEqual(manager.cycleActive().getDictionaryName(), 'Oxford');
+  assert.deepStrictEqual(manager.dictionaries.map(d => d.getDictionaryName()), ['Oxford', 'Collins', 'Longman']);
+});
+
+test('cycling a single dictionary keeps it active', () => {
+  const manager = managerWith('Oxford');
+  assert.strictEqual(manager.cycleActive().getDictionaryName(), 'Oxford');
+});
+
+test('grouped results put local dictionaries in load order before online results', () => {
+  const manager = managerWith('Oxford', 'Collins');
+  const groups = manager.groupResults([
+    { word: 'test', source: 'Free Dictionary', online: true },
+    { word: 'test', source: 'Collins' },
+    { word: 'tester', source: 'Oxford' },
+    { word: 'testing', source: 'Collins' },
+    { word: 'test', source: 'Oxford' }
+  ]);
+
+  assert.deepStrictEqual(groups.map(group => [group.label, group.online]), [
+    ['Oxford', false],
+    ['Collins', false],
+    ['Free Dictionary', true]
+  ]);
+  assert.deepStrictEqual(groups[0].results.map(result => result.word), ['tester', 'test']);
+  assert.deepStrictEqual(groups[1].results.map(result => result.word), ['test', 'testing']);
+});
+
+test('grouping no results returns no groups', () => {
+  assert.deepStrictEqual(managerWith('Oxford').groupResults([]), []);
+});
+
+test('search suggestions are ranked when there are enough prefix matches', () => {
+  const manager = managerWith('Oxford');
+  manager.prefixSearch = () => ['testament', 'tested', 'Test', 'testy', 'test', 'tests'].map(word => ({ word, source: 'Oxford' }));
+  manager.pinyinSearch = () => [];
+
+  assert.deepStrictEqual(manager.searchWords('test', 4).map(result => result.word), ['test', 'Test', 'testy', 'tests']);
+});
+
+// [输入, 建议, 分级]
+const SCORES = [
+  ['test', { word: 'test' }, 0],
+  [' test ', { word: 'test' }, 0],
+  ['test', { word: 'Test' }, 1],
+  ['TEST', { word: 'test' }, 1],
+  ['test', { word: 'testing' }, 2],
+  ['test', { word: 'tset', distance: 2 }, 3],
+  ['test', { word: 'Test', distance: 0 }, 1]
+];
+
+test('scoreSuggestion grades exact, case-insensitive, prefix and fuzzy matches', () => {
+  SCORES.forEach(([query, result, score]) => {
+    assert.strictEqual(DictionaryManager.scoreSuggestion(query, result), score, `${query} / ${result.word}`);
+  });
+});
+
+// [输入, 建议, 排序后的词（来源）]
+const RANKINGS = [
+  ['test', [{ word: 'testing' }, { word: 'Test' }, { word: 'test' }], ['test', 'Test', 'testing']],
+  ['test', [{ word: 'testament' }, { word: 'tests' }], ['tests', 'testament']],
+  ['test', [{ word: 'tast', distance: 1 }, { word: 'testy' }, { word: 'tent', distance: 2 }], ['testy', 'tast', 'tent']],
+  ['test', [{ word: 'toast', distance: 2 }, { word: 'tent', distance: 2 }], ['tent', 'toast']],
+  ['test', [{ word: 'tests', source: 'Free Dictionary', online: true }, { word: 'tests', source: 'Oxford' }], ['tests (Oxford)', 'tests (Free Dictionary)']],
+  ['test', [{ word: 'test', source: 'Free Dictionary', online: true }, { word: 'test', source: 'Oxford' }], ['test (Oxford)', 'test (Free Dictionary)']]
+];
+
+test('rankSuggestions orders by grade, then distance and length, then local before online', () => {
+  RANKINGS.forEach(([query, results, expected]) => {
+    const ranked = DictionaryManager.rankSuggestions(query, results)
+      .map(result => (result.source ? `${result.word} (${result.source})` : result.word));
+    assert.deepStrictEqual(ranked, expected, JSON.stringify(results));
+  });
+});
+
+// [样式表, 限定后的样式表]
+const SCOPED_CSS = [
+  ['.def { color: red; }', '.d .def { color: red; }'],
+  ['body, .def b { margin: 0; }', '.d, .d .def b { margin: 0; }'],
+  ['html body p { margin: 0; }', '.d body p { margin: 0; }'],
+  ['@media print { .def { color: black; } }', '@media print {.d .def { color: black; } }'],
+  ['@font-face { font-family: X; }', '@font-face { font-family: X; }'],
+  ['@charset "utf-8"; /* note */ .def { }', '@charset "utf-8";.d .def { }']
+];
+
+test('stylesheets are scoped to a selector, keeping at-rules intact', () => {
+  SCOPED_CSS.forEach(([css, expected]) => {
+    assert.strictEqual(MdictParser.scopeCss(css, '.d'), expected, css);
+  });
+});
+
+test('dictionaries with conflicting stylesheets render in independently styled sections', async () => {
+  const dir = tempDir();
+  const configs = ['red', 'blue'].map(color => {
+    const css = path.join(dir, `${color}.css`);
+    fs.writeFileSync(css, `.def { color: ${color}; }`);
+    return { mdx: writeDictionary(path.join(dir, `${color}.mdx`), [['cat', `<p class="def">${color} cat</p>`]]), css };
+  });
+  const manager = new DictionaryManager(configs);
+  await manager.load();
+
+  const html = await manager.lookup('cat');
+  assert.match(html, /<div class="dictionary-section dictionary-0">[\s\S]*red cat[\s\S]*<div class="dictionary-section dictionary-1">[\s\S]*blue cat/);
+  assert.match(html, /\.dictionary-0 \.def \{ color: red; \}/);
+  assert.match(html, /\.dictionary-1 \.def \{ color: blue; \}/);
+  assert.doesNotMatch(html, /^\s*\.def \{/m);
+});
@@ -0,0 +1,98 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const zlib = require('zlib');
+const { tempDir } = require('./helpers');
+const { readZipEntries, unpackDictionaryPackage } = require('../src/dictionary-package');
+
+// 写出 ZIP：files 为 [文件名, 内容, 是否 deflate 压缩]
+function writeZip(file, files) {
+  const locals = [];
+  const centrals = [];
+  let offset = 0;
+  files.forEach(([name, content, deflate]) => {
+    const data = Buffer.from(content);
+    const stored = deflate ? zlib.deflateRawSync(data) : data;
+    const nameBytes = Buffer.from(name, 'utf-8');
+    const fields = (header, start) => {
+      header.writeUInt16LE(0x800, start);
+      header.writeUInt16LE(deflate ? 8 : 0, start + 2);
+      header.writeUInt32LE(zlib.crc32(data), start + 8);
+      header.writeUInt32LE(stored.length, start + 12);
+      header.writeUInt32LE(data.length, start + 16);
+      header.writeUInt16LE(nameBytes.length, start + 20);
+    };
+
+    const local = Buffer.alloc(30);
+    local.writeUInt32LE(0x04034b50, 0);
+    fields(local, 6);
+    const central = Buffer.alloc(46);
+    central.writeUInt32LE(0x02014b50, 0);
+    fields(central, 8);
+    central.writeUInt32LE(offset, 42);
+
+    locals.push(local, nameBytes, stored);
+    centrals.push(central, nameBytes);
+    offset += local.length + nameBytes.length + stored.length;
+  });
+
+  const directory = Buffer.concat(centrals);
+  const end = Buffer.alloc(22);
+  end.writeUInt32LE(0x06054b50, 0);
+  end.writeUInt16LE(files.length, 8);
+  end.writeUInt16LE(files.length, 10);
+  end.writeUInt32LE(directory.length, 12);
+  end.writeUInt32LE(offset, 16);
+  fs.writeFileSync(file, Buffer.concat([...locals, directory, end]));
+  return file;
+}
+
+test('a zipped dictionary is unpacked with the MDD and CSS that match the MDX', async () => {
+  const dir = tempDir();
+  const zip = writeZip(path.join(dir, 'oxford.zip'), [
+    ['readme.txt', 'hello', false],
+    ['oxford/extra.mdd', 'other resources', true],
+    ['oxford/Oxford.mdx', 'dictionary', false],
+    ['oxford/Oxford.mdd', 'resources '.repeat(100), true],
+    ['style.css', '.def { color: red; }', true]
+  ]);
+  const target = path.join(dir, 'unpacked');
+
+  assert.deepStrictEqual((await readZipEntries(zip)).map(entry => [entry.name, entry.method]), [
+    ['readme.txt', 0], ['oxford/extra.mdd', 8], ['oxford/Oxford.mdx', 0], ['oxford/Oxford.mdd', 8], ['style.css', 8]
+  ]);
+
+  const files = await unpackDictionaryPackage(zip, target);
+  assert.deepStrictEqual(files, {
+    mdx: path.join(target, 'oxford', 'Oxford.mdx'),
+    mdd: path.join(target, 'oxford', 'Oxford.mdd'),
+    css: path.join(target, 'oxford', 'style.css')
+  });
+  assert.strictEqual(fs.readFileSync(files.mdx, 'utf-8'), 'dictionary');
+  assert.strictEqual(fs.readFileSync(files.mdd, 'utf-8'), 'resources '.repeat(100));
+  assert.strictEqual(fs.readFileSync(files.css, 'utf-8'), '.def { color: red; }');
+
+  // 已解压且没有变化的文件不再解压
+  const extractedAt = fs.statSync(files.mdd).mtimeMs;
+  await unpackDictionaryPackage(zip, target);
+  assert.strictEqual(fs.statSync(files.mdd).mtimeMs, extractedAt);
+});
+
+test('zip entries are extracted inside the target folder and bad archives are reported', async () => {
+  const dir = tempDir();
+  const target = path.join(dir, 'unpacked');
+  const escaping = writeZip(path.join(dir, 'evil.zip'), [['../../evil.mdx', 'dictionary', false]]);
+  assert.deepStrictEqual(await unpackDictionaryPackage(escaping, target), {
+    mdx: path.join(target, 'evil', 'evil.mdx'),
+    mdd: null,
+    css: null
+  });
+
+  const noMdx = writeZip(path.join(dir, 'empty.zip'), [['style.css', '', false]]);
+  await assert.rejects(unpackDictionaryPackage(noMdx, target), /"empty\.zip" does not contain an MDX file/);
+
+  const notZip = path.join(dir, 'plain.zip');
+  fs.writeFileSync(notZip, 'not an archive at all');
+  await assert.rejects(unpackDictionaryPackage(notZip, target), /"plain\.zip" is not a zip archive/);
+});
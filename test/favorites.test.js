@@ -0,0 +1,59 @@
+const test = require('node:test');
+const assert = require('node:assert');
+require('./helpers');
+const Favorites = require('../src/favorites');
+
+test('round-trips a newline word list', () => {
+  const favorites = new Favorites();
+  favorites.add('apple', 'Oxford');
+  favorites.add('banana split', 'Oxford');
+
+  const text = Favorites.format(favorites.list(), 'txt');
+  assert.strictEqual(text, 'banana split\napple\n');
+
+  const imported = new Favorites();
+  const { entries, skipped } = Favorites.parse(text, 'txt');
+  assert.strictEqual(skipped, 0);
+  assert.strictEqual(imported.importEntries(entries), 2);
+  assert.deepStrictEqual(imported.list().map(entry => entry.word), ['banana split', 'apple']);
+});
+
+test('parses an Eudic CSV word book and skips malformed lines', () => {
+  const csv = [
+    '\uFEFF单词,释义',
+    'abandon,"v. 放弃, 抛弃"',
+    '"say ""hi""",打招呼',
+    '"unterminated,oops',
+    ',no word',
+    '',
+    'zeal,n. 热情'
+  ].join('\r\n');
+
+  const { entries, skipped } = Favorites.parse(csv, 'csv');
+  assert.deepStrictEqual(entries, [
+    { word: 'abandon', note: 'v. 放弃, 抛弃' },
+    { word: 'say "hi"', note: '打招呼' },
+    { word: 'zeal', note: 'n. 热情' }
+  ]);
+  assert.strictEqual(skipped, 2);
+});
+
+test('round-trips an Eudic XML word book', () => {
+  const entries = [{ word: 'R&D', note: '<research>' }, { word: 'test', note: '' }];
+  const parsed = Favorites.parse(Favorites.format(entries, 'xml'), 'xml');
+  assert.deepStrictEqual(parsed, { entries, skipped: 0 });
+});
+
+test('import merges with existing favorites without duplicates', () => {
+  const favorites = new Favorites();
+  favorites.add('Apple', 'Oxford');
+  const added = favorites.importEntries([{ word: 'apple' }, { word: 'pear' }]);
+  assert.strictEqual(added, 1);
+  assert.deepStrictEqual(favorites.list().map(entry => entry.word), ['pear', 'Apple']);
+});
+
+test('skips over-long and control-character lines in a word list', () => {
+  const { entries, skipped } = Favorites.parse(`ok\n${'x'.repeat(201)}\nbad\u0001word\n`, 'txt');
+  assert.deepStrictEqual(entries.map(entry => entry.word), ['ok']);
+  assert.strictEqual(skipped, 2);
+});
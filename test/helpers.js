@@ -0,0 +1,271 @@
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const Module = require('module');
+
+// 内存中的 electron-store 替代品，只实现应用用到的方法。
+// MemoryStore.presets[name] 为新建实例的初始内容，用于在加载被测模块之前准备配置；
+// MemoryStore.instances[name] 为最近创建的同名实例，用于在测试中修改被测模块的配置
+class MemoryStore {
+  constructor(options = {}) {
+    const name = options.name || 'config';
+    this.path = path.join(options.cwd || os.tmpdir(), `${name}.json`);
+    this.data = JSON.parse(JSON.stringify(MemoryStore.presets[name] || {}));
+    MemoryStore.instances[name] = this;
+  }
+
+  get(key, defaultValue) {
+    return Object.prototype.hasOwnProperty.call(this.data, key) ? this.data[key] : defaultValue;
+  }
+
+  set(key, value) {
+    this.data[key] = value;
+  }
+
+  has(key) {
+    return Object.prototype.hasOwnProperty.call(this.data, key);
+  }
+
+  delete(key) {
+    delete this.data[key];
+  }
+
+  clear() {
+    this.data = {};
+  }
+}
+
+MemoryStore.presets = {};
+MemoryStore.instances = {};
+
+// 内存中的 js-mdict 替代品：mdictFiles[文件路径] = { header, entries: [[词头, 释义]], fields }，
+// 同一词头可以出现多次，fields（可选）为 js-mdict 解析出的其他字段（如 keyHeader）。
+// 文件本身由 writeDictionary 写出（只有文件头），用于通过文件头校验。
+// 打开过的文件路径按顺序记录在 MemoryMdict.opened 中
+const mdictFiles = {};
+
+class MemoryMdict {
+  constructor(file) {
+    const data = mdictFiles[file];
+    if (!data) {
+      throw new Error(`ENOENT: no such file or directory, open '${file}'`);
+    }
+    MemoryMdict.opened.push(file);
+    this.header = data.header || {};
+    this.entries = data.entries;
+    this.keywordList = data.entries.map(([keyText], index) => ({ keyText, recordStartOffset: index }));
+    Object.assign(this, data.fields);
+  }
+
+  lookup(word) {
+    const entry = this.entries.find(([keyText]) => keyText === word);
+    return entry ? { keyText: entry[0], definition: entry[1] } : { keyText: word, definition: null };
+  }
+
+  locate(key) {
+    return this.lookup(key);
+  }
+
+  fetch_definition(item) {
+    const [keyText, definition] = this.entries[item.recordStartOffset];
+    return { keyText, definition };
+  }
+
+  suggest(prefix) {
+    return this.keywordList.filter(item => item.keyText.startsWith(prefix));
+  }
+}
+
+MemoryMdict.opened = [];
+
+// 写出词典文件（4 字节长度 + UTF-16 头部 + adler32，之后是 blocks），并登记其词条；返回文件路径
+function writeDictionary(file, entries, attributes = {}, blocks = Buffer.alloc(0)) {
+  const header = { GeneratedByEngineVersion: '2.0', Encoding: 'UTF-8', ...attributes };
+  const xml = `<Dictionary ${Object.entries(header).map(([key, value]) => `${key}="${value}"`).join(' ')}/>\r\n\0`;
+  const body = Buffer.from(xml, 'utf16le');
+  const length = Buffer.alloc(4);
+  length.writeUInt32BE(body.length);
+  const checksum = Buffer.alloc(4);
+  checksum.writeUInt32LE(adler32(body));
+
+  fs.mkdirSync(path.dirname(file), { recursive: true });
+  fs.writeFileSync(file, Buffer.concat([length, body, checksum, blocks]));
+  // 主进程按真实路径打开词典文件
+  const realPath = fs.realpathSync(file);
+  mdictFiles[realPath] = { header, entries };
+  return realPath;
+}
+
+// 2.0 格式的一个键块和一个记录块，块开头 4 字节为压缩方式（0 无、1 LZO、2 zlib），内容由 js-mdict 替身提供
+function blockLayout(entryCount, keyCompression, recordCompression) {
+  const numbers = values => Buffer.concat(values.map(value => {
+    const buffer = Buffer.alloc(8);
+    buffer.writeBigUInt64BE(BigInt(value));
+    return buffer;
+  }));
+  const block = compression => {
+    const buffer = Buffer.alloc(8);
+    buffer.writeUInt32LE(compression);
+    return buffer;
+  };
+  return Buffer.concat([
+    numbers([1, entryCount, 0, 0, 8]), Buffer.alloc(4), block(keyCompression),
+    numbers([1, entryCount, 0, 8]), block(recordCompression)
+  ]);
+}
+
+function adler32(buffer) {
+  let a = 1;
+  let b = 0;
+  for (let i = 0; i < buffer.length; i++) {
+    a = (a + buffer[i]) % 65521;
+    b = (b + a) % 65521;
+  }
+  return ((b << 16) | a) >>> 0;
+}
+
+function tempDir() {
+  return fs.mkdtempSync(path.join(os.tmpdir(), 'quickdict-'));
+}
+
+// ipcMain 替代品：记录主进程注册的处理函数，测试中通过 invoke / send 直接调用
+const ipcMain = {
+  handlers: new Map(),
+  listeners: new Map(),
+  handle(channel, handler) {
+    this.handlers.set(channel, handler);
+  },
+  on(channel, listener) {
+    this.listeners.set(channel, listener);
+  }
+};
+
+// BrowserWindow 替代品：只记录加载的文件、显示状态和发给页面的消息（webContents.sent），不渲染页面。
+// 截图固定为 BrowserWindow.capture（默认为 PNG 文件头），所有创建过的窗口在 instances 中
+class BrowserWindow {
+  constructor(options = {}) {
+    this.options = options;
+    this.loaded = null;
+    this.loadedContent = null;
+    this.destroyed = false;
+    this.shown = false;
+    this.focused = false;
+    const sent = [];
+    this.webContents = {
+      sent,
+      send: (channel, data) => sent.push([channel, data]),
+      executeJavaScript: async () => 300,
+      capturePage: async () => ({ toPNG: () => BrowserWindow.capture })
+    };
+    BrowserWindow.instances.push(this);
+  }
+
+  static getAllWindows() {
+    return BrowserWindow.instances.filter(win => !win.destroyed);
+  }
+
+  async loadFile(file) {
+    this.loaded = file;
+    this.loadedContent = fs.existsSync(file) ? fs.readFileSync(file, 'utf8') : null;
+  }
+
+  async loadURL(url) {
+    this.loaded = url;
+  }
+
+  setContentSize() {}
+
+  on() {}
+
+  isMinimized() {
+    return false;
+  }
+
+  restore() {}
+
+  show() {
+    this.shown = true;
+  }
+
+  focus() {
+    this.focused = true;
+  }
+
+  isDestroyed() {
+    return this.destroyed;
+  }
+
+  destroy() {
+    this.destroyed = true;
+  }
+}
+
+BrowserWindow.instances = [];
+BrowserWindow.capture = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+
+// electron 只能在 Electron 进程中使用，测试中用可替换的对象代替；
+// net.fetch 由需要的测试自行设置。app.whenReady 不会完成，不创建窗口
+const electron = {
+  app: {
+    getVersion: () => '0.0.0-test',
+    getPath: () => os.tmpdir(),
+    whenReady: () => new Promise(() => {}),
+    on: () => {},
+    quit: () => {}
+  },
+  net: {},
+  ipcMain,
+  BrowserWindow,
+  protocol: {
+    registerSchemesAsPrivileged: () => {},
+    handle: () => {}
+  },
+  globalShortcut: {
+    register: () => true,
+    unregisterAll: () => {},
+    isRegistered: () => false
+  },
+  clipboard: {
+    readText: () => ''
+  },
+  dialog: {},
+  screen: {}
+};
+
+const STUBS = {
+  electron,
+  'electron-store': MemoryStore,
+  'js-mdict': { MDX: MemoryMdict, MDD: MemoryMdict }
+};
+
+// 在被测模块 require 之前替换上述模块
+const originalLoad = Module._load;
+Module._load = function(request, ...args) {
+  if (Object.prototype.hasOwnProperty.call(STUBS, request)) {
+    return STUBS[request];
+  }
+  return originalLoad.call(this, request, ...args);
+};
+
+// 加载主进程模块：config 为主配置的初始内容。返回 invoke（ipcMain.handle 注册的命令）和
+// send（ipcMain.on 注册的消息），event.sender 为不接收消息的替身
+function loadMain(config = {}) {
+  MemoryStore.presets.config = { schemaVersion: 2, ...config };
+  require('../src/main');
+  const event = { sender: { send: () => {}, isDestroyed: () => false } };
+  return {
+    invoke: (channel, ...args) => ipcMain.handlers.get(channel)(event, ...args),
+    send: (channel, ...args) => ipcMain.listeners.get(channel)(event, ...args)
+  };
+}
+
+module.exports = {
+  MemoryStore,
+  MemoryMdict,
+  electron,
+  mdictFiles,
+  writeDictionary,
+  blockLayout,
+  tempDir,
+  loadMain
+};
@@ -0,0 +1,24 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const MdictParser = require('../src/mdict-parser');
+
+test('a guard rejects a word already visited in the same dictionary', () => {
+  const guard = MdictParser.createLookupGuard();
+  assert.strictEqual(guard.visit('Colour', 'a.mdx'), true);
+  assert.strictEqual(guard.visit('color', 'a.mdx'), true);
+  assert.strictEqual(guard.visit(' colour ', 'a.mdx'), false);
+});
+
+test('one guard lets the same word be looked up in every dictionary', () => {
+  const guard = MdictParser.createLookupGuard();
+  assert.strictEqual(guard.visit('colour', 'a.mdx'), true);
+  assert.strictEqual(guard.visit('colour', 'b.mdx'), true);
+});
+
+test('a guard limits chained lookups per dictionary', () => {
+  const guard = MdictParser.createLookupGuard(2);
+  assert.strictEqual(guard.visit('one', 'a.mdx'), true);
+  assert.strictEqual(guard.visit('two', 'a.mdx'), true);
+  assert.strictEqual(guard.visit('three', 'a.mdx'), false);
+  assert.strictEqual(guard.visit('three', 'b.mdx'), true);
+});
@@ -0,0 +1,45 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const MdictParser = require('../src/mdict-parser');
+
+// 内存中的 MDX 替身：entries 为 [词头, 释义]，同一词头可以出现多次
+function parserWith(entries) {
+  const parser = new MdictParser('dict.mdx');
+  parser.mdx = {
+    header: {},
+    keywordList: entries.map(([keyText], index) => ({ keyText, index })),
+    lookup: word => {
+      const entry = entries.find(([keyText]) => keyText === word);
+      return entry ? { keyText: entry[0], definition: entry[1] } : { keyText: word, definition: null };
+    },
+    fetch_definition: item => ({ keyText: entries[item.index][0], definition: entries[item.index][1] })
+  };
+  return parser;
+}
+
+test('a trimmed query uses the matched headword for the title and homographs', async () => {
+  const parser = parserWith([['run', '<p>verb</p>'], ['run', '<p>noun</p>']]);
+  const { entry } = await parser.lookupSection('run,');
+
+  assert.strictEqual(entry.displayWord, 'run');
+  assert.strictEqual(entry.redirected, false);
+  assert.match(entry.definition, /verb[\s\S]*homograph-separator[\s\S]*noun/);
+});
+
+test('a redirect shows the banner and the target headword', async () => {
+  const parser = parserWith([['ran', '@@@LINK=run'], ['run', '<p>verb</p>'], ['run', '<p>noun</p>']]);
+  parser.updateDisplaySettings({ showRedirectBanner: true });
+  const { entry, html } = await parser.lookupSection('ran');
+
+  assert.strictEqual(entry.displayWord, 'run');
+  assert.strictEqual(entry.redirected, true);
+  assert.match(entry.definition, /noun/);
+  assert.match(html, /redirected from/);
+});
+
+test('no redirect banner when only punctuation was trimmed', async () => {
+  const parser = parserWith([['run', '<p>verb</p>']]);
+  parser.updateDisplaySettings({ showRedirectBanner: true });
+  const { html } = await parser.lookupSection('run.');
+  assert.doesNotMatch(html, /redirected from/);
+});
@@ -0,0 +1,334 @@
+const test = require('node:test');
+const { mock } = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const { MemoryStore, MemoryMdict, electron, writeDictionary, blockLayout, tempDir, loadMain } = require('./helpers');
+
+const dir = tempDir();
+const mdxFile = writeDictionary(path.join(dir, 'test.mdx'), [
+  ['apple', '<b>apple</b> a fruit'],
+  ['apply', '<b>apply</b> to put to use']
+]);
+const { invoke, send } = loadMain({ mdxFile, mddFile: '', cssFile: '', offlineMode: true });
+
+test('commands wait for the first load instead of failing while it runs', async () => {
+  const [first, second] = await Promise.all([invoke('prefix-search', 'app', 10), invoke('prefix-search', 'appl', 10)]);
+  assert.ok(first.result.length > 0);
+  assert.ok(second.result.length > 0);
+});
+
+test('benchmark-lookups keeps using its dictionary when a reload starts mid-call', async () => {
+  await invoke('reload-dictionary');
+
+  const benchmark = invoke('benchmark-lookups', ['apple', 'apply'], 3);
+  const reload = invoke('reload-dictionary');
+
+  const [report, reloaded] = await Promise.all([benchmark, reload]);
+  assert.strictEqual(report.result.lookups, 6);
+  assert.ok(reloaded.result);
+});
+
+test('commands that require the dictionary survive a reload started mid-call', async () => {
+  await invoke('reload-dictionary');
+
+  const search = invoke('prefix-search', 'app', 10);
+  const reload = invoke('reload-dictionary');
+
+  const [found] = await Promise.all([search, reload]);
+  assert.deepStrictEqual(found.error, undefined);
+  assert.ok(found.result.length > 0);
+});
+
+test('export-definition-image suggests a file name without path separators or reserved names', async () => {
+  const suggested = [];
+  electron.dialog.showSaveDialog = async (options) => {
+    suggested.push(options.defaultPath);
+    return { canceled: true };
+  };
+
+  for (const word of ['../etc/passwd', 'a:b*c?', 'con', '...']) {
+    assert.strictEqual(await invoke('export-definition-image', word), null);
+  }
+  assert.deepStrictEqual(suggested, ['.._etc_passwd.png', 'a_b_c_.png', '_con.png', 'QuickDict.png']);
+});
+
+test('export-definition-image renders the definition from a temporary file and writes a PNG', async () => {
+  const file = path.join(tempDir(), 'apple.png');
+  assert.strictEqual(await invoke('export-definition-image', 'apple', file), file);
+
+  const png = fs.readFileSync(file);
+  assert.deepStrictEqual([...png.subarray(0, 8)], [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+
+  const offscreen = electron.BrowserWindow.instances.at(-1);
+  assert.strictEqual(path.extname(offscreen.loaded), '.html');
+  assert.match(offscreen.loadedContent, /a fruit/);
+  assert.ok(offscreen.destroyed);
+  assert.strictEqual(fs.existsSync(offscreen.loaded), false);
+});
+
+test('health-check does not probe online providers in offline mode', async () => {
+  electron.net.fetch = async () => assert.fail('no request expected');
+  const report = await invoke('health-check');
+  assert.deepStrictEqual(report.online, { offline: true, providers: [] });
+  assert.strictEqual(report.dictionary.loaded, true);
+});
+
+test('benchmark-lookups rejects invalid iterations and caps large ones', async () => {
+  for (const iterations of [0, -1, NaN, Infinity, '5', null]) {
+    const response = await invoke('benchmark-lookups', ['apple'], iterations);
+    assert.strictEqual(response.error && response.error.code, 'InvalidArgument', String(iterations));
+  }
+  assert.strictEqual((await invoke('benchmark-lookups', 'apple')).error.code, 'InvalidArgument');
+  assert.strictEqual((await invoke('benchmark-lookups', ['apple'], 2.7)).result.lookups, 2);
+  assert.strictEqual((await invoke('benchmark-lookups', ['apple'], 1e9)).result.lookups, 1000);
+});
+
+test('definition-diff compares definitions without opening resource files', async () => {
+  const diffDir = tempDir();
+  const fileA = writeDictionary(path.join(diffDir, 'a.mdx'), [['apple', 'a red fruit']]);
+  const fileB = writeDictionary(path.join(diffDir, 'b.mdx'), [['apple', 'a green fruit']]);
+  // 无法解析的 MDD：被打开时文件头校验会失败
+  fs.writeFileSync(path.join(diffDir, 'a.mdd'), 'not a dictionary');
+  fs.writeFileSync(path.join(diffDir, 'b.1.mdd'), 'not a dictionary');
+
+  const diff = await invoke('definition-diff', 'apple', fileA, fileB);
+  assert.ok(diff);
+  assert.strictEqual(diff.foundInA, true);
+  assert.strictEqual(diff.foundInB, true);
+  assert.ok(diff.segments.length > 1);
+});
+
+test('dictionary overrides can be listed and reset per dictionary', async () => {
+  send('set-dictionary-override', 'unescapeEntities', true);
+  send('set-dictionary-override', 'definitionTemplate', '<p>{definition}</p>');
+
+  assert.deepStrictEqual(await invoke('get-dictionary-overrides'), {
+    unescapeEntities: true,
+    definitionTemplate: '<p>{definition}</p>'
+  });
+  assert.deepStrictEqual(await invoke('get-dictionary-overrides', 'other.mdx'), {});
+
+  assert.deepStrictEqual(await invoke('reset-dictionary-overrides', 'other.mdx'), {});
+  assert.strictEqual((await invoke('get-dictionary-overrides', 'test.mdx')).unescapeEntities, true);
+  assert.deepStrictEqual(await invoke('reset-dictionary-overrides'), {});
+  assert.deepStrictEqual(await invoke('get-dictionary-overrides', 'test.mdx'), {});
+});
+
+test('a failed online search keeps the local results and flags the online group', async () => {
+  send('set-offline-mode', false);
+  electron.net.fetch = async () => {
+    throw new Error('getaddrinfo ENOTFOUND');
+  };
+  try {
+    const { result: groups } = await invoke('search-words-grouped', 'app', 10, true);
+    assert.deepStrictEqual(groups[0].results.map(result => result.word), ['apple', 'apply']);
+    assert.strictEqual(groups.length, 2);
+    assert.strictEqual(groups[1].online, true);
+    assert.deepStrictEqual(groups[1].results.map(result => [result.unavailable, result.error]), [[true, 'getaddrinfo ENOTFOUND']]);
+  } finally {
+    send('set-offline-mode', true);
+  }
+});
+
+// 等待窗口收到 channel 消息（查询等操作不返回 Promise），返回最后一条消息的内容
+async function waitForMessage(win, channel) {
+  for (let i = 0; i < 100; i++) {
+    const messages = win.webContents.sent.filter(([name]) => name === channel);
+    if (messages.length > 0) {
+      return messages.at(-1)[1];
+    }
+    await new Promise(resolve => setTimeout(resolve, 5));
+  }
+  throw new Error(`no ${channel} message`);
+}
+
+// 记录注册的全局快捷键，返回 { 快捷键: 处理函数 }
+function captureHotkeys() {
+  const registered = {};
+  electron.globalShortcut.register = (accelerator, handler) => {
+    registered[accelerator] = handler;
+    return true;
+  };
+  electron.globalShortcut.unregisterAll = () => {
+    Object.keys(registered).forEach(key => delete registered[key]);
+  };
+  return registered;
+}
+
+test('the global hotkey opens the main window or looks up the clipboard depending on the target', async () => {
+  const registered = captureHotkeys();
+  const windowsBefore = electron.BrowserWindow.instances.length;
+
+  send('set-hotkey-target', 'main');
+  registered['Alt+M']();
+  const mainWindow = electron.BrowserWindow.instances.at(-1);
+  assert.strictEqual(electron.BrowserWindow.instances.length, windowsBefore + 1);
+  assert.strictEqual(mainWindow.loaded, 'src/index.html');
+  assert.ok(mainWindow.shown && mainWindow.focused);
+
+  electron.clipboard.readText = () => 'apple';
+  send('set-hotkey-target', 'lookup');
+  registered['Alt+M']();
+  const lookupWindow = electron.BrowserWindow.instances.at(-1);
+  assert.strictEqual(lookupWindow.loaded, 'src/lookup.html');
+  const message = await waitForMessage(lookupWindow, 'lookup-result');
+  assert.strictEqual(message.word, 'apple');
+  assert.match(message.result, /a fruit/);
+});
+
+test('a moved dictionary is found under the dictionaries root and relinked on reload', async () => {
+  const config = MemoryStore.instances.config;
+  const root = tempDir();
+  fs.mkdirSync(path.join(root, 'english'));
+  const moved = writeDictionary(path.join(root, 'english', 'moved.mdx'), [['pear', 'a fruit']]);
+  config.set('mdxFile', path.join(tempDir(), 'gone', 'moved.mdx'));
+  config.set('dictionariesRoot', root);
+
+  try {
+    const { result: info } = await invoke('reload-dictionary');
+    assert.strictEqual(info[0].file, 'moved.mdx');
+    assert.strictEqual(config.get('mdxFile'), moved);
+    assert.deepStrictEqual((await invoke('prefix-search', 'pe', 10)).result.map(result => result.word), ['pear']);
+  } finally {
+    config.set('mdxFile', mdxFile);
+    config.delete('dictionariesRoot');
+    await invoke('reload-dictionary');
+  }
+});
+
+test('resource-mime answers from the resource name without reading the record', async () => {
+  const locate = MemoryMdict.prototype.locate;
+  MemoryMdict.prototype.locate = () => assert.fail('the resource should not be read');
+  try {
+    assert.strictEqual(await invoke('resource-mime', 'sound/uk/apple.MP3'), 'audio/mpeg');
+    assert.strictEqual(await invoke('resource-mime', 'img\\apple.png'), 'image/png');
+    assert.strictEqual(await invoke('resource-mime', 'apple.xyz'), 'application/octet-stream');
+  } finally {
+    MemoryMdict.prototype.locate = locate;
+  }
+});
+
+test('changes to the dictionary files trigger a single reload once the files stop changing', async () => {
+  const watched = [];
+  const watch = fs.watch;
+  fs.watch = (file, listener) => {
+    watched.push([file, listener]);
+    return { close: () => {} };
+  };
+  mock.timers.enable({ apis: ['setTimeout'] });
+  const observer = new electron.BrowserWindow();
+
+  try {
+    send('toggle-auto-reload', true);
+    assert.deepStrictEqual(watched.map(([file]) => file), [mdxFile]);
+
+    // 连续写入只触发一次重新加载
+    const [[, changed]] = watched;
+    changed('change');
+    mock.timers.tick(600);
+    changed('change');
+    mock.timers.tick(1000);
+    await new Promise(resolve => setImmediate(resolve));
+    mock.timers.tick(500);
+    mock.timers.reset();
+    const reloaded = await waitForMessage(observer, 'dictionary-reloaded');
+
+    assert.deepStrictEqual(reloaded, { success: true, name: 'test' });
+    assert.strictEqual(observer.webContents.sent.filter(([channel]) => channel === 'dictionary-reloaded').length, 1);
+  } finally {
+    send('toggle-auto-reload', false);
+    mock.timers.reset();
+    fs.watch = watch;
+    observer.destroy();
+  }
+});
+
+test('a newer online search cancels the one still in flight', async () => {
+  send('set-offline-mode', false);
+  const aborted = [];
+  electron.net.fetch = (url, options) => new Promise((resolve, reject) => {
+    const word = decodeURIComponent(url.split('/').pop());
+    const abort = () => {
+      clearTimeout(timer);
+      aborted.push(word);
+      reject(new Error('aborted'));
+    };
+    const timer = setTimeout(() => resolve({ status: 200, ok: true, json: async () => [{ word, meanings: [] }] }), 30);
+    // 与 net.fetch 一样，已经取消的请求立即失败
+    if (options.signal.aborted) {
+      abort();
+    }
+    options.signal.addEventListener('abort', abort);
+  });
+
+  try {
+    const stale = invoke('search-online', 'appl');
+    const latest = invoke('search-online', 'apple');
+    assert.deepStrictEqual(await stale, { result: null });
+    assert.deepStrictEqual((await latest).result.map(result => result.word), ['apple']);
+    assert.deepStrictEqual(aborted, ['appl']);
+  } finally {
+    send('set-offline-mode', true);
+  }
+});
+
+test('online searches use the configured Free Dictionary language', async () => {
+  const urls = [];
+  electron.net.fetch = async (url) => {
+    urls.push(url);
+    return { status: 200, ok: true, json: async () => [{ word: 'café', meanings: [] }] };
+  };
+  send('set-offline-mode', false);
+  try {
+    send('set-online-language', 'fr');
+    assert.deepStrictEqual((await invoke('search-online', 'café')).result.map(result => result.word), ['café']);
+    send('set-online-language', 'en');
+    await invoke('search-online', 'cafe');
+    assert.deepStrictEqual(urls, [
+      'https://api.dictionaryapi.dev/api/v2/entries/fr/caf%C3%A9',
+      'https://api.dictionaryapi.dev/api/v2/entries/en/cafe'
+    ]);
+  } finally {
+    send('set-offline-mode', true);
+  }
+});
+
+test('validate-dictionary reports on a file without replacing the loaded dictionary', async () => {
+  const validateDir = tempDir();
+  const other = writeDictionary(
+    path.join(validateDir, 'other.mdx'),
+    [['pear', 'a fruit'], ['plum', 'a fruit']],
+    { Title: 'Fruits', Encoding: 'GBK' },
+    blockLayout(2, 2, 1)
+  );
+  const locked = writeDictionary(path.join(validateDir, 'locked.mdx'), [['pear', 'a fruit']], { Encrypted: '1' });
+  const text = path.join(validateDir, 'notes.txt');
+  fs.writeFileSync(text, 'not a dictionary');
+
+  const { result: report } = await invoke('validate-dictionary', other);
+  assert.deepStrictEqual(report, {
+    valid: true,
+    error: null,
+    file: 'other.mdx',
+    type: 'mdx',
+    title: 'Fruits',
+    version: '2.0',
+    encoding: 'GBK',
+    encrypted: false,
+    compression: { keyBlocks: 'zlib', recordBlocks: 'lzo' },
+    entryCount: 2
+  });
+
+  const { result: lockedReport } = await invoke('validate-dictionary', locked);
+  assert.strictEqual(lockedReport.valid, false);
+  assert.strictEqual(lockedReport.encrypted, true);
+  assert.match(lockedReport.error, /is encrypted/);
+  assert.match((await invoke('validate-dictionary', text)).result.error, /"notes\.txt" is not an MDX or MDD file/);
+
+  electron.dialog.showOpenDialog = async () => ({ canceled: true, filePaths: [] });
+  assert.deepStrictEqual(await invoke('validate-dictionary'), { result: null });
+
+  assert.deepStrictEqual((await invoke('prefix-search', 'app', 10)).result.map(result => result.word), ['apple', 'apply']);
+  assert.deepStrictEqual((await invoke('prefix-search', 'pe', 10)).result, []);
+});
@@ -0,0 +1,612 @@
+const test = require('node:test');
+const { mock } = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const path = require('path');
+const { MemoryMdict, mdictFiles, writeDictionary, blockLayout, tempDir } = require('./helpers');
+const MdictParser = require('../src/mdict-parser');
+
+// 用 helpers 中的 js-mdict 替身加载词典：entries 为 [词头, 释义]，header 为 MDX 头部属性
+async function loadParser(entries, header = {}, displaySettings = {}, name = 'dict.mdx') {
+  const file = writeDictionary(path.join(tempDir(), name), entries, header);
+  const parser = new MdictParser(file, null, null, displaySettings);
+  await parser.load();
+  return parser;
+}
+
+test('the source badge shows the escaped dictionary title only when enabled', async () => {
+  const parser = await loadParser([['cat', '<p>a pet</p>']], { Title: 'Pets & Co' });
+
+  assert.doesNotMatch((await parser.lookupSection('cat')).html, /source-badge/);
+  parser.updateDisplaySettings({ showSourceBadge: true });
+  assert.match((await parser.lookupSection('cat')).html, /<div class="source-badge">Pets &amp; Co<\/div>/);
+});
+
+test('the source badge falls back to the file name for the placeholder title', async () => {
+  const parser = await loadParser([['cat', '<p>a pet</p>']], { Title: 'Title (No HTML code allowed)' }, { showSourceBadge: true }, 'animals.mdx');
+  assert.strictEqual(parser.getDictionaryName(), 'animals');
+  assert.match((await parser.lookupSection('cat')).html, /<div class="source-badge">animals<\/div>/);
+});
+
+test('double-escaped records are unescaped only when the dictionary override is on', async () => {
+  const parser = await loadParser([
+    ['cat', '&lt;b&gt;cat&lt;/b&gt; &amp;lt;pet&amp;gt;'],
+    ['dog', '<b>dog</b> &lt;pet&gt;']
+  ]);
+
+  assert.match((await parser.lookupSection('cat')).html, /&lt;b&gt;cat/);
+  parser.updateOverrides({ unescapeEntities: true });
+  assert.match((await parser.lookupSection('cat')).html, /<b>cat<\/b> &lt;pet&gt;/);
+  // 已经是 HTML 的记录保持原样
+  assert.match((await parser.lookupSection('dog')).html, /<b>dog<\/b> &lt;pet&gt;/);
+});
+
+test('restyling the current entry applies new display settings without another lookup', async () => {
+  const parser = await loadParser([['cat', '<p>a pet</p>']], {}, { fontSize: '14' });
+  assert.strictEqual(parser.restyleCurrent(), null);
+
+  await parser.lookup('cat');
+  parser.mdx.lookup = () => assert.fail('the record should not be read again');
+  parser.updateDisplaySettings({ fontSize: '20', lineHeight: '2' });
+
+  const restyled = parser.restyleCurrent();
+  assert.match(restyled, /font-size: 20px/);
+  assert.match(restyled, /line-height: 2;/);
+  assert.match(restyled, /<p>a pet<\/p>/);
+});
+
+// [设置的后备字体, 规范化结果]
+const FONT_FALLBACKS = [
+  ['Arial, "Noto Sans", sans-serif', ['Arial', 'Noto Sans', 'sans-serif']],
+  [['Arial', 'Arial', ' Georgia '], ['Arial', 'Georgia']],
+  [['bad;font', 'x}{', 'Ok'], ['Ok']],
+  [null, MdictParser.DEFAULT_FONT_FALLBACKS]
+];
+
+test('font fallbacks are normalized and generic families are not quoted', () => {
+  FONT_FALLBACKS.forEach(([fallbacks, expected]) => {
+    assert.deepStrictEqual(MdictParser.normalizeFontFallbacks(fallbacks), expected, JSON.stringify(fallbacks));
+  });
+
+  const parser = new MdictParser('dict.mdx', null, null, { fontFamily: 'Segoe UI', fontFallbacks: 'Noto Sans, serif' });
+  assert.strictEqual(parser.getFontFamilyStack(), "'Segoe UI', 'Noto Sans', serif");
+  assert.match(parser.wrapPage(''), /font-family: 'Segoe UI', 'Noto Sans', serif;/);
+});
+
+test('coverage diff counts shared and missing headwords with limited samples', () => {
+  const dir = tempDir();
+  const fileA = writeDictionary(path.join(dir, 'a.mdx'), [['cat', ''], ['dog', ''], ['dog', ''], ['emu', ''], ['fox', '']]);
+  const fileB = writeDictionary(path.join(dir, 'b.mdx'), [['ant', ''], ['cat', ''], ['fox', ''], ['gnu', '']]);
+
+  assert.deepStrictEqual(MdictParser.coverageDiff(fileA, fileB, 1), {
+    onlyA: { count: 2, sample: ['dog'] },
+    onlyB: { count: 2, sample: ['ant'] },
+    shared: { count: 2, sample: ['cat'] }
+  });
+});
+
+test('entries are read lazily in dictionary order, homographs included', async () => {
+  const parser = await loadParser([['run', '<p>verb</p>'], ['run', '<p>noun</p>'], ['walk', '<p>verb</p>']]);
+  let reads = 0;
+  const fetch = parser.mdx.fetch_definition.bind(parser.mdx);
+  parser.mdx.fetch_definition = item => {
+    reads++;
+    return fetch(item);
+  };
+
+  const iterator = parser.entries();
+  assert.strictEqual(reads, 0);
+  assert.deepStrictEqual(iterator.next().value, { word: 'run', definition: '<p>verb</p>' });
+  assert.strictEqual(reads, 1);
+  assert.deepStrictEqual([...iterator].map(entry => entry.definition), ['<p>noun</p>', '<p>verb</p>']);
+
+  assert.throws(() => new MdictParser('x.mdx').entries().next(), /not loaded/);
+});
+
+test('records are used exactly as js-mdict decodes them, without re-decoding', async () => {
+  // js-mdict 按头部的 Encoding 解码记录后才返回字符串，QuickDict 拿不到原始字节
+  const mixed = '<p>中文 café ��</p>';
+  const parser = await loadParser([['word', mixed]], { Encoding: 'UTF-8' });
+
+  assert.match((await parser.lookupSection('word')).html, new RegExp(mixed));
+  assert.strictEqual(parser.lookupText('word'), '中文 café ��');
+});
+
+test('the last headword in the dictionary returns its complete definition', async () => {
+  // 记录大小由 js-mdict 计算，QuickDict 按词头或记录位置读取整条记录
+  const last = `<p>${'last entry '.repeat(200)}end</p>`;
+  const parser = await loadParser([['alpha', '<p>first</p>'], ['omega', last]]);
+
+  assert.match((await parser.lookupSection('omega')).html, new RegExp(last));
+  assert.strictEqual([...parser.entries()].at(-1).definition, last);
+});
+
+test('vocabulary levels are extracted with per-dictionary selectors and can order suggestions', async () => {
+  const parser = await loadParser([
+    ['cat', '<p>a pet <span class="level">B2</span></p>'],
+    ['cab', '<p>a taxi <span class="cefr">A1</span></p>'],
+    ['cap', '<p>a hat <i class="tag">HSK 3</i></p>'],
+    ['car', '<p>a vehicle</p>']
+  ]);
+
+  assert.strictEqual(parser.extractLevel('cat'), 'B2');
+  assert.strictEqual(parser.extractLevel('cap'), null);
+  assert.strictEqual(parser.extractLevel('car'), null);
+
+  parser.updateOverrides({ levelSelectors: ['tag'] });
+  assert.strictEqual(parser.extractLevel('cap'), 'HSK 3');
+  assert.strictEqual(parser.extractLevel('cat'), null);
+
+  parser.updateOverrides({});
+  assert.strictEqual(parser.getSuggestions('ca'), 'cat, cab, cap, car');
+  parser.updateOverrides({ sortSuggestionsByLevel: true });
+  assert.strictEqual(parser.getSuggestions('ca'), 'cab, cat, cap, car');
+});
+
+test('with resource debugging on, rewritten images and audio say whether the MDD has them', async () => {
+  const dir = tempDir();
+  const mdxFile = writeDictionary(path.join(dir, 'dict.mdx'), [
+    ['cat', '<img src="img/cat.png"><img src="img/dog.png"><a href="sound/cat.mp3">play</a>']
+  ]);
+  const mddFile = writeDictionary(path.join(dir, 'dict.mdd'), [['\\cat.png', 'png']]);
+  const parser = new MdictParser(mdxFile, mddFile, null);
+  await parser.load();
+
+  const plain = (await parser.lookupSection('cat')).html;
+  assert.doesNotMatch(plain, /data-debug|resource-missing/);
+
+  parser.updateDisplaySettings({ debugResources: true });
+  const html = (await parser.lookupSection('cat')).html;
+  assert.match(html, /<img data-debug="found" data-debug-src="img\/cat.png" data-debug-name="cat.png" src="mdd-resource:\/\/cat.png">/);
+  assert.match(html, /<img data-debug="missing" data-debug-src="img\/dog.png" data-debug-name="dog.png" src="mdd-resource:\/\/dog.png"><span class="resource-missing">\[missing: dog.png\]<\/span>/);
+  assert.match(html, /<a href="mdd-resource:\/\/cat.mp3" data-audio="true" data-debug="missing"/);
+});
+
+test('header attributes beyond title and description are kept as attribution metadata', async () => {
+  const parser = await loadParser([['cat', '<p>a pet</p>']], {
+    Title: 'Pets',
+    Description: 'All about pets',
+    Author: 'Jane Roe',
+    Register_by: 'EMail',
+    CustomNote: 'v2'
+  });
+
+  const metadata = parser.getMetadata();
+  assert.strictEqual(metadata.title, 'Pets');
+  assert.strictEqual(metadata.description, 'All about pets');
+  assert.strictEqual(metadata.author, 'Jane Roe');
+  assert.strictEqual(metadata.extra.Author, 'Jane Roe');
+  assert.strictEqual(metadata.extra.Register_by, 'EMail');
+  assert.strictEqual(metadata.extra.CustomNote, 'v2');
+  assert.strictEqual(metadata.extra.Title, undefined);
+});
+
+// [查询, 除数, 上限, 允许的编辑距离]
+const FUZZY_DISTANCES = [
+  ['cat', 4, 3, 1],
+  ['pronunciation', 4, 3, 3],
+  ['accommodate', 4, 3, 3],
+  ['dictionary', 4, 2, 2],
+  ['cat', 1, 5, 3]
+];
+
+test('the suggestion edit distance scales with query length within the configured cap', async () => {
+  const parser = await loadParser([
+    ['accommodate', ''], ['cat', ''], ['cot', ''], ['cut', ''], ['act', ''], ['cart', ''], ['dog', '']
+  ]);
+
+  FUZZY_DISTANCES.forEach(([word, divisor, cap, expected]) => {
+    parser.updateSearchOptions({ fuzzyDistanceDivisor: divisor, fuzzyDistanceCap: cap });
+    assert.strictEqual(parser.getFuzzyDistance(word), expected, `${word} / ${divisor} / ${cap}`);
+  });
+
+  parser.updateSearchOptions({});
+  // 少两个字母、错一个字母的长词仍能找到
+  assert.deepStrictEqual(parser.fuzzySearch('acomodatte').map(result => result.keyText), ['accommodate']);
+  // 三个字母的词只允许一处差异
+  assert.deepStrictEqual(parser.fuzzySearch('cat').map(result => result.keyText), ['cat', 'cot', 'cut', 'cart']);
+});
+
+test('query normalization follows the StripKey and KeyCaseSensitive header rules', async () => {
+  const stripping = await loadParser([['USA', ''], ['ice-cream', '']], { StripKey: 'Yes', KeyCaseSensitive: 'No' });
+  assert.deepStrictEqual(stripping.normalizeQuery('U.S.A.'), {
+    original: 'U.S.A.',
+    normalized: 'usa',
+    steps: [{ step: 'strip-key', result: 'USA' }, { step: 'case-fold', result: 'usa' }],
+    exists: true
+  });
+  assert.strictEqual(stripping.normalizeQuery('Ice Cream').normalized, 'icecream');
+  assert.strictEqual(stripping.normalizeQuery('Ice Cream').exists, true);
+
+  const plain = await loadParser([['USA', '']], { StripKey: 'No', KeyCaseSensitive: 'Yes' });
+  assert.deepStrictEqual(plain.normalizeQuery('U.S.A.'), { original: 'U.S.A.', normalized: 'U.S.A.', steps: [], exists: false });
+  assert.strictEqual(plain.normalizeQuery('usa').exists, false);
+});
+
+test('a definition template wraps bare-text entries with the headword and phonetic', async () => {
+  const parser = await loadParser([['café', 'a small restaurant /kæˈfeɪ/']]);
+  assert.match((await parser.lookupSection('café')).html, /<div class="word-title">café<\/div>\s*a small restaurant/);
+
+  parser.updateOverrides({ definitionTemplate: '<h2>{word}</h2><span class="ipa">{phonetic}</span><p>{definition}</p>' });
+  assert.match(
+    (await parser.lookupSection('café')).html,
+    /<h2>café<\/h2><span class="ipa">\/kæˈfeɪ\/<\/span><p>a small restaurant \/kæˈfeɪ\/<\/p>/
+  );
+});
+
+test('a record that js-mdict cannot slice shows an error instead of throwing', async () => {
+  // js-mdict 按记录偏移切片，偏移越界时抛出 RangeError
+  const dir = tempDir();
+  const mdxFile = writeDictionary(path.join(dir, 'dict.mdx'), [['cat', '<p>a pet</p>'], ['cow', '<img src="cow.png">']]);
+  const mddFile = writeDictionary(path.join(dir, 'dict.mdd'), [['\\cow.png', 'png']]);
+  const parser = new MdictParser(mdxFile, mddFile, null);
+  await parser.load();
+  const outOfRange = () => {
+    throw new RangeError('The value of "offset" is out of range. It must be >= 0 and <= 12. Received 4096');
+  };
+  parser.mdx.lookup = outOfRange;
+  parser.mdd.locate = outOfRange;
+
+  const { entry, html } = await parser.lookupSection('cat');
+  assert.strictEqual(entry, null);
+  assert.match(html, /Failed to lookup word: cat/);
+  assert.match(html, /Corrupt record block in dict\.mdx \(The value of &quot;offset&quot; is out of range/);
+  assert.strictEqual(parser.locateResource('cow.png'), null);
+});
+
+test('the preferred pronunciation region picks the matching audio and hides the other region', async () => {
+  const definition = '<span class="phons_br"><a class="sound" href="sound://uk/cat__gb_1.mp3">UK</a></span>' +
+    '<span class="phons_n_am"><a class="sound" href="sound://us/cat__us_1.mp3">US</a></span>';
+  const parser = await loadParser([['cat', definition]]);
+
+  assert.strictEqual(parser.selectRegionAudio(definition), 'sound://uk/cat__gb_1.mp3');
+  parser.updateDisplaySettings({ preferredRegion: 'us' });
+  assert.strictEqual(parser.selectRegionAudio(definition), 'sound://us/cat__us_1.mp3');
+  assert.match(parser.getRegionCss(), /^\.phons_br, .*\{ display: none !important; \}$/);
+  parser.updateDisplaySettings({ preferredRegion: 'uk' });
+  assert.strictEqual(parser.selectRegionAudio(definition), 'sound://uk/cat__gb_1.mp3');
+  assert.match(parser.getRegionCss(), /^\.phons_n_am, /);
+
+  // 文件名不带地区时按 class 判断
+  parser.updateDisplaySettings({ preferredRegion: 'us' });
+  assert.strictEqual(parser.selectRegionAudio('<a class="pron uk" href="a.mp3"></a><a class="pron us" href="b.mp3"></a>'), 'b.mp3');
+  assert.strictEqual(parser.selectRegionAudio('<p>no audio</p>'), null);
+});
+
+test('prefix search finds entries through the selected key transform while lookup stays exact', async () => {
+  const parser = await loadParser([['the cat', '<p>a pet</p>'], ['catch', '<p>to seize</p>'], ["cat's eye", '<p>a gem</p>']]);
+
+  assert.deepStrictEqual(parser.prefixSearch('cat').sort(), ["cat's eye", 'catch']);
+  assert.strictEqual(parser.buildPrefixIndex('stripArticles'), 4);
+  assert.deepStrictEqual(parser.prefixSearch('cat').sort(), ["cat's eye", 'catch', 'the cat']);
+  assert.deepStrictEqual(parser.prefixSearch('cats'), []);
+
+  parser.buildPrefixIndex('stripPunctuation');
+  assert.deepStrictEqual(parser.prefixSearch('cats'), ["cat's eye"]);
+
+  assert.match((await parser.lookupSection('cat')).html, /<div class="not-found">/);
+  assert.match((await parser.lookupSection('the cat')).html, /a pet/);
+});
+
+// [释义, 词性, 过滤结果]
+const POS_FILTERS = [
+  ['<h1>run</h1><span class="pos">verb</span><p>to move fast</p><span class="pos">noun</span><p>a jog</p>', 'noun',
+    '<h1>run</h1><span class="pos">noun</span><p>a jog</p>'],
+  ['<h1>run</h1><span class="pos">verb</span><p>to move fast</p><span class="pos">noun</span><p>a jog</p>', 'v',
+    '<h1>run</h1><span class="pos">verb</span><p>to move fast</p>'],
+  ['<span class="pos">verb</span><p>to move fast</p><span class="pos">noun</span><p>a jog</p>', 'adjective',
+    '<span class="pos">verb</span><p>to move fast</p><span class="pos">noun</span><p>a jog</p>'],
+  ['<span class="pos">verb</span><p>to move fast</p>', 'noun', '<span class="pos">verb</span><p>to move fast</p>']
+];
+
+test('filtering by part of speech keeps only the matching sections of a structured entry', async () => {
+  POS_FILTERS.forEach(([html, pos, expected]) => {
+    assert.strictEqual(new MdictParser('dict.mdx').filterPos(html, pos), expected, pos);
+  });
+
+  const parser = await loadParser([['run', POS_FILTERS[0][0]]]);
+  await parser.lookup('run');
+  parser.lastEntry.pos = 'noun';
+  const filtered = parser.restyleCurrent();
+  assert.match(filtered, /a jog/);
+  assert.doesNotMatch(filtered, /to move fast/);
+});
+
+test('rendered entries are served from the render cache until display settings change', async () => {
+  const parser = await loadParser([['cat', '<p>a pet</p>'], ['dog', '<p>a friend</p>']], {}, { renderCacheBytes: 100000, theme: 'light' });
+  let renders = 0;
+  const build = parser.buildEntryBody.bind(parser);
+  parser.buildEntryBody = (...args) => {
+    renders++;
+    return build(...args);
+  };
+
+  const first = (await parser.lookupSection('cat')).html;
+  assert.strictEqual((await parser.lookupSection('cat')).html, first);
+  await parser.lookupSection('dog');
+  await parser.lookupSection('cat');
+  assert.strictEqual(renders, 2);
+
+  parser.updateDisplaySettings({ renderCacheBytes: 100000, theme: 'dark' });
+  await parser.lookupSection('cat');
+  await parser.lookupSection('cat');
+  assert.strictEqual(renders, 3);
+
+  // 上限为 0 时不缓存
+  parser.updateDisplaySettings({ renderCacheBytes: 0 });
+  await parser.lookupSection('cat');
+  await parser.lookupSection('cat');
+  assert.strictEqual(renders, 5);
+});
+
+test('a resource file loaded as the dictionary is rejected with a clear error', async () => {
+  const dir = tempDir();
+  const resources = [['\\img\\cat.png', 'png'], ['\\img\\dog.png', 'png'], ['\\sound\\cat.mp3', 'mp3']];
+  const mddFile = writeDictionary(path.join(dir, 'pets.mdd'), resources);
+  const disguised = writeDictionary(path.join(dir, 'pets.mdx'), resources);
+
+  await assert.rejects(new MdictParser(mddFile).load(), /"pets\.mdd" is a resource file \(MDD\), not a dictionary \(MDX\)/);
+  const parser = new MdictParser(disguised);
+  await assert.rejects(parser.load(), /"pets\.mdx" is a resource file \(MDD\), not a dictionary \(MDX\)/);
+  assert.strictEqual(parser.mdx, null);
+
+  // MDX 放在资源位置时忽略它，词典本身照常加载
+  const mdxFile = writeDictionary(path.join(dir, 'animals.mdx'), [['cat', '<p>a pet</p>']]);
+  const other = writeDictionary(path.join(dir, 'other.mdx'), [['dog', '<p>a friend</p>']]);
+  const withMdxResources = new MdictParser(mdxFile, other);
+  await withMdxResources.load();
+  assert.strictEqual(withMdxResources.mdd, null);
+  assert.match((await withMdxResources.lookupSection('cat')).html, /a pet/);
+});
+
+test('the redirect banner can be reworded or turned off while the resolved entry is shown', async () => {
+  const parser = await loadParser([['colour', '@@@LINK=color'], ['color', '<p>hue</p>']]);
+
+  let html = (await parser.lookupSection('colour')).html;
+  assert.match(html, /<div class="redirect-info">\(redirected from &quot;colour&quot;\)<\/div>/);
+  assert.match(html, /<div class="word-title">color<\/div>/);
+
+  parser.updateDisplaySettings({ redirectBannerText: 'See also <{word}>' });
+  html = (await parser.lookupSection('colour')).html;
+  assert.match(html, /<div class="redirect-info">See also &lt;colour&gt;<\/div>/);
+
+  parser.updateDisplaySettings({ showRedirectBanner: false });
+  html = (await parser.lookupSection('colour')).html;
+  assert.doesNotMatch(html, /redirect-info/);
+  assert.match(html, /<p>hue<\/p>/);
+});
+
+test('LZO-compressed dictionaries are accepted and their blocks decoded by js-mdict', async () => {
+  // QuickDict 不自己解压：只读取压缩方式用于检查报告，解压由 js-mdict 完成
+  const dir = tempDir();
+  const entries = [['cat', '<p>a pet</p>'], ['dog', '<p>a friend</p>']];
+  const lzo = writeDictionary(path.join(dir, 'lzo.mdx'), entries, {}, blockLayout(2, 1, 1));
+  const mixed = writeDictionary(path.join(dir, 'mixed.mdx'), entries, {}, blockLayout(2, 2, 1));
+
+  assert.deepStrictEqual(MdictParser.inspectFile(lzo).compression, { keyBlocks: 'lzo', recordBlocks: 'lzo' });
+  assert.strictEqual(MdictParser.inspectFile(lzo).valid, true);
+  assert.deepStrictEqual(MdictParser.inspectFile(mixed).compression, { keyBlocks: 'zlib', recordBlocks: 'lzo' });
+
+  const parser = new MdictParser(lzo);
+  await parser.load();
+  assert.match((await parser.lookupSection('dog')).html, /<p>a friend<\/p>/);
+});
+
+test('keys and records of GBK and UTF-16 dictionaries arrive decoded and are used as is', async () => {
+  // js-mdict 按头部的 Encoding 解码，QuickDict 只处理解码后的字符串
+  for (const encoding of ['GBK', 'UTF-16']) {
+    const parser = await loadParser([['苹果', '<p>一种水果</p>'], ['苹果树', '<p>结苹果的树</p>']], { Encoding: encoding }, {}, `${encoding}.mdx`);
+    assert.strictEqual(MdictParser.inspectFile(parser.mdxFile).encoding, encoding);
+    assert.match((await parser.lookupSection('苹果')).html, /<div class="word-title">苹果<\/div>\s*<p>一种水果<\/p>/);
+    assert.deepStrictEqual(parser.prefixSearch('苹').sort(), ['苹果', '苹果树']);
+  }
+
+  // 头部属性：2.x 为 UTF-16LE，3.x 为 UTF-8
+  assert.strictEqual(MdictParser.readHeaderAttribute(Buffer.from('<Dictionary Encoding="GBK"/>', 'utf16le'), 'Encoding'), 'GBK');
+  assert.strictEqual(MdictParser.readHeaderAttribute(Buffer.from('<Dictionary Encoding="UTF-16"/>', 'utf-8'), 'Encoding'), 'UTF-16');
+});
+
+test('truncated and corrupt dictionary headers are reported before parsing', async () => {
+  const dir = tempDir();
+  const file = writeDictionary(path.join(dir, 'good.mdx'), [['cat', '<p>a pet</p>']]);
+  const bytes = fs.readFileSync(file);
+  const variant = (name, data) => {
+    const target = writeDictionary(path.join(dir, name), [['cat', '<p>a pet</p>']]);
+    fs.writeFileSync(target, data);
+    return target;
+  };
+
+  assert.doesNotThrow(() => MdictParser.verifyHeader(file));
+
+  const tiny = variant('tiny.mdx', bytes.subarray(0, 3));
+  assert.throws(() => MdictParser.verifyHeader(tiny), /"tiny\.mdx" is too small to be a dictionary file/);
+
+  const truncated = variant('truncated.mdx', bytes.subarray(0, 40));
+  assert.throws(() => MdictParser.verifyHeader(truncated), /"truncated\.mdx" has an invalid header length \(\d+ bytes\) - file may be truncated/);
+
+  const huge = Buffer.from(bytes);
+  huge.writeUInt32BE(0xffffffff, 0);
+  assert.throws(() => MdictParser.verifyHeader(variant('huge.mdx', huge)), /invalid header length \(4294967295 bytes\)/);
+
+  const corrupt = Buffer.from(bytes);
+  corrupt[10] ^= 0xff;
+  const corruptFile = variant('corrupt.mdx', corrupt);
+  assert.throws(() => MdictParser.verifyHeader(corruptFile), /"corrupt\.mdx" header checksum mismatch - file may be corrupt/);
+  await assert.rejects(new MdictParser(corruptFile).load(), /header checksum mismatch/);
+});
+
+test('MDict 3.x dictionaries are reported as unsupported while 1.x and 2.x load', async () => {
+  const dir = tempDir();
+  for (const version of ['1.2', '2.0']) {
+    const file = writeDictionary(path.join(dir, `v${version}.mdx`), [['cat', '<p>a pet</p>']], { GeneratedByEngineVersion: version });
+    assert.doesNotThrow(() => MdictParser.verifyHeader(file), version);
+  }
+
+  const v3 = writeDictionary(path.join(dir, 'v3.mdx'), [['cat', '<p>a pet</p>']], { GeneratedByEngineVersion: '3.0' });
+  assert.throws(() => MdictParser.verifyHeader(v3), /"v3\.mdx" uses MDict format 3\.0, which is not supported yet/);
+  await assert.rejects(new MdictParser(v3).load(), /MDict format 3\.0/);
+  const report = MdictParser.inspectFile(v3);
+  assert.strictEqual(report.valid, false);
+  assert.strictEqual(report.version, '3.0');
+  assert.match(report.error, /not supported yet/);
+
+  // 3.x 的头部为 UTF-8
+  const xml = Buffer.from('<Dictionary GeneratedByEngineVersion="3.0" Encoding="UTF-8"/>', 'utf-8');
+  assert.strictEqual(MdictParser.readEngineVersion(xml), 3);
+});
+
+test('prefix matches collapse case variants and rank shorter and exact-case keys first', async () => {
+  const parser = await loadParser([['Catalog', ''], ['cat', ''], ['Cat', ''], ['cab', ''], ['Cabin', ''], ['CAT', ''], ['dog', '']]);
+
+  assert.deepStrictEqual(parser.prefixSearch('ca'), ['cab', 'cat', 'Cabin', 'Catalog']);
+  assert.deepStrictEqual(parser.prefixSearch('Ca'), ['Cat', 'cab', 'Cabin', 'Catalog']);
+  assert.deepStrictEqual(parser.prefixSearch('CA'), ['CAT', 'cab', 'Cabin', 'Catalog']);
+  assert.deepStrictEqual(parser.prefixSearch('ca', 2), ['cab', 'cat']);
+});
+
+test('dictionaries with encrypted records are rejected, encrypted key info is left to js-mdict', async () => {
+  const dir = tempDir();
+  for (const flag of ['Yes', '1', '3']) {
+    const file = writeDictionary(path.join(dir, `locked-${flag}.mdx`), [['cat', '<p>a pet</p>']], { Encrypted: flag });
+    assert.throws(() => MdictParser.verifyHeader(file), /"locked-\w+\.mdx" is encrypted and requires a registration key, which is not supported/, flag);
+    await assert.rejects(new MdictParser(file).load(), /is encrypted/);
+    assert.strictEqual(MdictParser.inspectFile(file).encrypted, true);
+  }
+
+  for (const flag of ['No', '0', '2']) {
+    const file = writeDictionary(path.join(dir, `open-${flag}.mdx`), [['cat', '<p>a pet</p>']], { Encrypted: flag });
+    const parser = new MdictParser(file);
+    await parser.load();
+    assert.match((await parser.lookupSection('cat')).html, /a pet/, flag);
+  }
+});
+
+test('js-mdict opens the dictionary and each resource volume by file path, in volume order', async () => {
+  // js-mdict 只接受文件路径，打包的词典先解压到磁盘再按路径打开
+  const dir = tempDir();
+  const mdxFile = writeDictionary(path.join(dir, 'dict.mdx'), [['cat', '<p>a pet</p>']]);
+  const volumes = ['dict.mdd', 'dict.2.mdd', 'dict.1.mdd']
+    .map(name => writeDictionary(path.join(dir, name), [[`\\${name}.png`, name]]));
+  MemoryMdict.opened.length = 0;
+
+  const parser = new MdictParser(mdxFile, volumes[0], null);
+  await parser.load();
+  assert.deepStrictEqual(MemoryMdict.opened, [mdxFile, volumes[0], volumes[2], volumes[1]]);
+  assert.strictEqual(parser.locateResource('dict.2.mdd.png'), 'dict.2.mdd');
+});
+
+// [文本, 查询选项, 折叠结果]
+const FOLDED_KEYS = [
+  ['Café', {}, 'Café'],
+  ['Café', { caseInsensitiveLookup: true }, 'café'],
+  ['Café', { ignoreAccents: true }, 'Cafe'],
+  [' Ærøskøbing ', { ignoreAccents: true, caseInsensitiveLookup: true }, 'ærøskøbing'],
+  ['Straße', { caseInsensitiveLookup: true }, 'strasse'],
+  ['naïve', { ignoreAccents: true }, 'naive']
+];
+
+test('lookups can ignore case and accents of accented headwords', async () => {
+  FOLDED_KEYS.forEach(([text, options, expected]) => {
+    assert.strictEqual(MdictParser.foldKey(text, options), expected, `${text} ${JSON.stringify(options)}`);
+  });
+
+  const parser = await loadParser([['Café', '<p>a coffee shop</p>'], ['résumé', '<p>a CV</p>'], ['resume', '<p>to continue</p>']]);
+  const found = async word => (await parser.lookupSection(word)).entry !== null;
+
+  assert.strictEqual(await found('cafe'), false);
+  assert.strictEqual(await found('CAFÉ'), false);
+
+  parser.updateSearchOptions({ ignoreAccents: true });
+  assert.strictEqual(await found('Cafe'), true);
+  assert.strictEqual(await found('cafe'), false);
+
+  parser.updateSearchOptions({ ignoreAccents: true, caseInsensitiveLookup: true });
+  assert.match((await parser.lookupSection('CAFE')).html, /<div class="word-title">Café<\/div>/);
+  // 完全匹配的词头优先于折叠后相同的词头
+  assert.match((await parser.lookupSection('resume')).html, /to continue/);
+  assert.match((await parser.lookupSection('résumé')).html, /a CV/);
+});
+
+test('briefs are plain text from tag-heavy definitions, following redirects', async () => {
+  const parser = await loadParser([
+    ['cat', '<style>.x{}</style><div class="entry"><span class="pos">n.</span> <b>A</b> small&nbsp;domesticated <i>carnivore</i> &amp; pet. It purrs.</div>'],
+    ['kitty', '@@@LINK=cat'],
+    ['猫', '<div><p>一种动物。常作宠物。</p></div>']
+  ]);
+
+  parser.updateSearchOptions({});
+  assert.strictEqual(parser.getBrief('cat'), 'n. A small domesticated carnivore & pet.');
+  assert.strictEqual(parser.getBrief('kitty'), 'n. A small domesticated carnivore & pet.');
+  assert.strictEqual(parser.getBrief('猫'), '一种动物。');
+  assert.strictEqual(parser.getBrief('dog'), '');
+
+  parser.updateSearchOptions({ briefFirstSentence: false, briefLength: 20 });
+  assert.strictEqual(parser.getBrief('cat'), 'n. A small domestica…');
+  assert.doesNotMatch(parser.getBrief('cat'), /[<>]/);
+});
+
+// 两个记录块的记录区信息（js-mdict 解析后的字段），词条偏移为 0 和 50
+function recordInfo(change = () => {}) {
+  const mdict = {
+    recordHeader: { recordBlocksNum: 2, recordBlockCompSize: 30 },
+    recordInfoList: [
+      { packSize: 10, unpackSize: 40, packAccumulateOffset: 0, unpackAccumulatorOffset: 0 },
+      { packSize: 20, unpackSize: 60, packAccumulateOffset: 10, unpackAccumulatorOffset: 40 }
+    ],
+    recordBlockDataStartOffset: 0
+  };
+  const keywordList = [{ keyText: 'cat', recordStartOffset: 0 }, { keyText: 'dog', recordStartOffset: 50 }];
+  change(mdict, keywordList);
+  return [mdict, keywordList];
+}
+
+// [修改, 错误信息]
+const RECORD_INFO_ERRORS = [
+  [mdict => { mdict.recordHeader.recordBlocksNum = 3; }, /the header lists 3 blocks but 2 were read/],
+  [mdict => { mdict.recordInfoList[1].packSize = 0; }, /block 1 has invalid sizes \(0 \/ 60\)/],
+  [mdict => { mdict.recordInfoList[1].unpackAccumulatorOffset = 39; }, /block 1 starts at the wrong offset/],
+  [mdict => { mdict.recordHeader.recordBlockCompSize = 31; }, /compressed block sizes add up to 30 bytes but the header lists 31/],
+  [mdict => { mdict.recordBlockDataStartOffset = 100; }, /record blocks extend past the end of the file/],
+  [(mdict, keywordList) => { keywordList[1].recordStartOffset = 100; }, /entry offset 100 is beyond the 100 bytes of decompressed record data/]
+];
+
+test('verifyRecordInfo reports inconsistent record block info as a corrupt file', () => {
+  const file = path.join(tempDir(), 'dict.mdx');
+  fs.writeFileSync(file, Buffer.alloc(120));
+
+  const [consistent, keywordList] = recordInfo();
+  assert.doesNotThrow(() => MdictParser.verifyRecordInfo(consistent, file, keywordList));
+  RECORD_INFO_ERRORS.forEach(([change, message]) => {
+    const [mdict, keywordList] = recordInfo(change);
+    assert.throws(() => MdictParser.verifyRecordInfo(mdict, file, keywordList), message);
+    assert.throws(() => MdictParser.verifyRecordInfo(mdict, file, keywordList), /^Error: "dict\.mdx" has inconsistent record block info: .* - file may be corrupt$/);
+  });
+});
+
+test('a dictionary with fewer entries than advertised loads with a warning naming the short key block', async () => {
+  const warn = mock.method(console, 'warn', () => {});
+  try {
+    const mdxFile = writeDictionary(path.join(tempDir(), 'short.mdx'), [['ant', '<p>an insect</p>'], ['bee', '<p>a pollinator</p>'], ['cat', '<p>a pet</p>']]);
+    mdictFiles[mdxFile].fields = {
+      keyHeader: { entriesNum: 5 },
+      keyBlockInfoList: [{ keyBlockEntriesNum: 2 }, { keyBlockEntriesNum: 3 }]
+    };
+    const parser = new MdictParser(mdxFile);
+    await parser.load();
+
+    assert.deepStrictEqual(warn.mock.calls.map(call => call.arguments[0]), [
+      '"short.mdx" lists 5 entries but 3 were read (key block 1 of 2 is incomplete); entries that could not be read will not be found'
+    ]);
+    assert.match((await parser.lookupSection('cat')).html, /a pet/);
+
+    // 没有各键块的词条数时只报告总数
+    warn.mock.resetCalls();
+    MdictParser.checkKeyBlocks({ keyHeader: { entriesNum: 4 } }, mdxFile, parser.getKeywordList());
+    assert.match(warn.mock.calls[0].arguments[0], /lists 4 entries but 3 were read; /);
+    warn.mock.resetCalls();
+    MdictParser.checkKeyBlocks({ keyHeader: { entriesNum: 3 } }, mdxFile, parser.getKeywordList());
+    assert.strictEqual(warn.mock.callCount(), 0);
+  } finally {
+    warn.mock.restore();
+  }
+});
@@ -0,0 +1,34 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const fs = require('fs');
+const os = require('os');
+const path = require('path');
+require('./helpers');
+const OnlineCache = require('../src/online-cache');
+
+const DATA = {
+  entries: {
+    'freeDictionary:e.g.': { word: 'e.g.', html: '<p>for example — 例如</p>'.repeat(50), timestamp: 1700000000000 }
+  }
+};
+
+test('a compressed cache file decompresses to the same content', () => {
+  const file = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'quickdict-')), 'online-cache.json');
+  fs.writeFileSync(file, OnlineCache.serialize(DATA, true));
+
+  const text = fs.readFileSync(file, 'utf8');
+  assert.ok(text.startsWith(OnlineCache.COMPRESSED_PREFIX));
+  assert.ok(Buffer.byteLength(text) < Buffer.byteLength(OnlineCache.serialize(DATA, false)));
+  assert.deepStrictEqual(OnlineCache.deserialize(text), DATA);
+});
+
+test('an uncompressed legacy cache file still loads', () => {
+  assert.deepStrictEqual(OnlineCache.deserialize(JSON.stringify(DATA, null, '\t')), DATA);
+});
+
+test('logical size counts the uncompressed content', () => {
+  const cache = new OnlineCache(168, true);
+  cache.set('freeDictionary', 'test', { word: 'test', html: '<p>test</p>' });
+  const usage = cache.diskUsage();
+  assert.strictEqual(usage.logicalBytes, Buffer.byteLength(OnlineCache.serialize({ entries: cache.getEntries() }, false)));
+});
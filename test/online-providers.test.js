@@ -0,0 +1,152 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { electron } = require('./helpers');
+const { FreeDictionaryProvider, probeProviders, setRequestConcurrency } = require('../src/online-providers');
+
+// 模拟 net.fetch：记录同时进行的请求数，每个请求延迟 delayMs 后返回 body
+function mockFetch(body, delayMs = 20) {
+  const stats = { active: 0, maxActive: 0, urls: [] };
+  electron.net.fetch = async (url, options) => {
+    stats.urls.push(url);
+    stats.active++;
+    stats.maxActive = Math.max(stats.maxActive, stats.active);
+    try {
+      await new Promise((resolve, reject) => {
+        const timer = setTimeout(resolve, delayMs);
+        options.signal.addEventListener('abort', () => {
+          clearTimeout(timer);
+          reject(new Error('aborted'));
+        });
+      });
+    } finally {
+      stats.active--;
+    }
+    return { status: 200, ok: true, json: async () => body };
+  };
+  return stats;
+}
+
+const SAMPLE = [{ word: 'test', meanings: [] }];
+
+test('with max concurrency 1 overlapping lookups run one after another', async () => {
+  setRequestConcurrency(1);
+  const stats = mockFetch(SAMPLE);
+  const provider = new FreeDictionaryProvider();
+  await Promise.all([provider.lookup('one'), provider.lookup('two'), provider.lookup('three')]);
+  assert.strictEqual(stats.maxActive, 1);
+  assert.strictEqual(stats.urls.length, 3);
+  assert.match(stats.urls[0], /\/one$/);
+});
+
+test('with max concurrency 2 at most two lookups run at once', async () => {
+  setRequestConcurrency(2);
+  const stats = mockFetch(SAMPLE);
+  const provider = new FreeDictionaryProvider();
+  await Promise.all(['a', 'b', 'c', 'd'].map(word => provider.lookup(word)));
+  assert.strictEqual(stats.maxActive, 2);
+});
+
+test('a request cancelled while queued leaves the queue', async () => {
+  setRequestConcurrency(1);
+  const stats = mockFetch(SAMPLE);
+  const provider = new FreeDictionaryProvider();
+  const controller = new AbortController();
+  const first = provider.lookup('first');
+  const queued = provider.lookup('queued', controller.signal);
+  controller.abort();
+
+  await assert.rejects(queued, { name: 'AbortError' });
+  await first;
+  assert.strictEqual(stats.urls.length, 1);
+
+  // 名额已归还，之后的请求仍能进行
+  await provider.lookup('after');
+  assert.strictEqual(stats.urls.length, 2);
+});
+
+// dictionaryapi.dev 返回的数据（节选）
+const API_RESPONSE = [{
+  word: 'hello',
+  phonetics: [{ audio: '' }, { text: '/həˈləʊ/' }],
+  meanings: [
+    {
+      partOfSpeech: 'noun',
+      definitions: [{ definition: '"Hello!" or an equivalent greeting.', synonyms: [] }]
+    },
+    {
+      partOfSpeech: 'interjection',
+      definitions: [{ definition: 'A greeting said when meeting someone.', example: 'Hello, everyone.' }]
+    }
+  ],
+  license: { name: 'CC BY-SA 3.0' }
+}];
+
+test('maps an API response into structured entries', async () => {
+  setRequestConcurrency(2);
+  mockFetch(API_RESPONSE, 0);
+  const result = await new FreeDictionaryProvider().lookupEntries('hello');
+  assert.deepStrictEqual(result, {
+    word: 'hello',
+    entries: [{
+      word: 'hello',
+      phonetic: '/həˈləʊ/',
+      meanings: [
+        {
+          partOfSpeech: 'noun',
+          definitions: [{ definition: '"Hello!" or an equivalent greeting.', example: '' }]
+        },
+        {
+          partOfSpeech: 'interjection',
+          definitions: [{ definition: 'A greeting said when meeting someone.', example: 'Hello, everyone.' }]
+        }
+      ]
+    }]
+  });
+});
+
+test('the rendered lookup is built from the same structured entries', async () => {
+  mockFetch(API_RESPONSE, 0);
+  const entry = await new FreeDictionaryProvider().lookup('hello');
+  assert.strictEqual(entry.entries.length, 1);
+  assert.match(entry.html, /<div class="pos">interjection<\/div>/);
+  assert.match(entry.html, /&quot;Hello!&quot;/);
+});
+
+test('malformed responses yield no entries', () => {
+  assert.deepStrictEqual(FreeDictionaryProvider.parseEntries({ title: 'No Definitions Found' }), []);
+  assert.deepStrictEqual(FreeDictionaryProvider.parseEntries([null, { word: 'x' }]), [{ word: 'x', phonetic: '', meanings: [] }]);
+});
+
+test('probing reports each provider as reachable on any HTTP response', async () => {
+  setRequestConcurrency(2);
+  electron.net.fetch = async () => ({ status: 404, ok: false });
+  const results = await probeProviders({ language: 'de' });
+
+  assert.deepStrictEqual(results.map(({ name, reachable, status, error }) => ({ name, reachable, status, error })), [
+    { name: 'freeDictionary', reachable: true, status: 404, error: null },
+    { name: 'translation', reachable: false, status: null, error: 'Not configured' }
+  ]);
+});
+
+test('probing gives up after its own short timeout', async () => {
+  mockFetch(API_RESPONSE, 1000);
+  const [result] = await probeProviders({}, 50);
+
+  assert.strictEqual(result.reachable, false);
+  assert.match(result.error, /timed out after 0.05s/);
+  assert.ok(result.ms < 1000);
+});
+
+test('Free Dictionary URLs and cache names follow the configured language', async () => {
+  const stats = mockFetch(SAMPLE, 0);
+  const french = new FreeDictionaryProvider({ language: 'fr' });
+  await french.lookup(' café ');
+  assert.strictEqual(stats.urls[0], 'https://api.dictionaryapi.dev/api/v2/entries/fr/caf%C3%A9');
+  assert.strictEqual(french.cacheName, 'freeDictionary:fr');
+
+  const fallback = new FreeDictionaryProvider({ language: 'xx' });
+  await fallback.lookup('cat');
+  assert.strictEqual(stats.urls[1], 'https://api.dictionaryapi.dev/api/v2/entries/en/cat');
+  assert.strictEqual(fallback.cacheName, 'freeDictionary');
+  assert.strictEqual(new FreeDictionaryProvider().language, 'en');
+});
@@ -0,0 +1,47 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const MdictParser = require('../src/mdict-parser');
+
+function parserWith(words) {
+  const parser = new MdictParser('dict.mdx');
+  parser.mdx = { header: {}, keywordList: words.map((keyText, index) => ({ keyText, index })) };
+  return parser;
+}
+
+// [模式, 是否接受]
+const PATTERNS = [
+  ['colo*r', true],
+  ['/^un.*able$/', true],
+  ['/(colou?r)s?/', true],
+  ['/(?:ab)+/', true],
+  ['/(a+)+$/', false],
+  ['/((a*))*b/', false],
+  ['/(a{2,})+/', false],
+  ['/([a-z]+)\\1/', false],
+  [`${'a'.repeat(MdictParser.MAX_PATTERN_LENGTH)}*`, false]
+];
+
+test('patternToRegExp rejects nested quantifiers, back-references and overlong patterns', () => {
+  PATTERNS.forEach(([pattern, accepted]) => {
+    assert.strictEqual(MdictParser.patternToRegExp(pattern) !== null, accepted, pattern);
+  });
+});
+
+test('a rejected pattern returns no results instead of scanning', () => {
+  const parser = parserWith(['a'.repeat(30) + '!']);
+  assert.deepStrictEqual(parser.patternSearch('/(a+)+$/'), []);
+});
+
+test('patternSearch stops at the time budget even within the first keys', () => {
+  const parser = parserWith(Array.from({ length: 500 }, (_, i) => `word${i}`));
+  const now = Date.now;
+  let calls = 0;
+  // 每次读取时间推进 10ms，预算为 25ms 时只能扫描几个词头
+  Date.now = () => now() + 10 * calls++;
+  try {
+    const results = parser.patternSearch('/^word/', 1000, 25);
+    assert.ok(results.length > 0 && results.length < 10, `${results.length} results`);
+  } finally {
+    Date.now = now;
+  }
+});
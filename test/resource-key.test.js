@@ -0,0 +1,90 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const MdictParser = require('../src/mdict-parser');
+
+// 只有头部和按键查找的 MDD 分卷替身
+function fakeMdd(caseSensitive, keys) {
+  return {
+    header: { KeyCaseSensitive: caseSensitive ? 'Yes' : 'No' },
+    locate: key => (keys.includes(key) ? { keyText: key, definition: Buffer.from(key) } : null)
+  };
+}
+
+test('resource keys follow the case sensitivity of the volume being searched', () => {
+  const parser = new MdictParser('dict.mdx');
+  const first = fakeMdd(false, []);
+  const second = fakeMdd(true, ['\\img\\Cat.png']);
+  parser.mdd = first;
+  parser.mdds = [first, second];
+
+  assert.strictEqual(parser.normalizeResourceKey('\\img\\Cat.png', first), 'img/cat.png');
+  assert.strictEqual(parser.normalizeResourceKey('\\img\\Cat.png', second), 'img/Cat.png');
+  assert.strictEqual(parser.normalizeResourceKey('/img/Cat.png'), 'img/cat.png');
+  assert.deepStrictEqual(parser.locateResourceIn(second, '/img/Cat.png'), Buffer.from('\\img\\Cat.png'));
+});
+
+// [mdd-resource 地址, 资源名]
+const URLS = [
+  ['mdd-resource://img/cat.png', 'img/cat.png'],
+  ['mdd-resource://img/black%20cat.png', 'img/black cat.png'],
+  ['mdd-resource://sound/%E4%BD%A0%E5%A5%BD.mp3', 'sound/你好.mp3'],
+  ['mdd-resource://img/cat.png?v=2#top', 'img/cat.png'],
+  ['mdd-resource://img/100%.png', 'img/100%.png']
+];
+
+test('resource names are decoded from mdd-resource URLs before normalizing', () => {
+  URLS.forEach(([url, name]) => {
+    assert.strictEqual(MdictParser.resourceNameFromUrl(url), name, url);
+  });
+
+  const parser = new MdictParser('dict.mdx');
+  const mdd = fakeMdd(false, ['\\img\\black cat.png']);
+  parser.mdd = mdd;
+  parser.mdds = [mdd];
+  const name = MdictParser.resourceNameFromUrl('mdd-resource://img/Black%20Cat.png');
+  assert.deepStrictEqual(parser.locateResourceIn(mdd, name), Buffer.from('\\img\\black cat.png'));
+});
+
+// 带键列表的 MDD 替身：按键查找时只匹配完整的键
+function mddWithKeys(caseSensitive, keys) {
+  return { ...fakeMdd(caseSensitive, keys), keywordList: keys.map(keyText => ({ keyText })) };
+}
+
+test('a record stored under several names is found by each of them', () => {
+  const parser = new MdictParser('dict.mdx');
+  const mdd = mddWithKeys(false, ['\\img\\a.png;\\img\\b.png', '\\sound\\my word.mp3', '\\note one two']);
+  parser.mdd = mdd;
+  parser.mdds = [mdd];
+
+  assert.deepStrictEqual(parser.locateResource('img/a.png'), Buffer.from('\\img\\a.png;\\img\\b.png'));
+  assert.deepStrictEqual(parser.locateResource('\\img\\b.png'), Buffer.from('\\img\\a.png;\\img\\b.png'));
+  assert.deepStrictEqual(parser.locateResource('sound/my word.mp3'), Buffer.from('\\sound\\my word.mp3'));
+  // 不是每一部分都像文件名时不拆分
+  assert.strictEqual(parser.locateResource('one'), null);
+  assert.strictEqual(parser.locateResource('word.mp3'), null);
+});
+
+// [请求的资源名, 找到的键（null 为找不到）]
+const SEPARATORS_AND_CASE = [
+  ['IMG/Cat.PNG', '\\img\\cat.png'],
+  ['img\\cat.png', '\\img\\cat.png'],
+  ['\\IMG\\CAT.png', '\\img\\cat.png'],
+  ['/img/cat.png', '\\img\\cat.png'],
+  ['img/dog.png', null]
+];
+
+test('resource names match keys regardless of separator style and, when allowed, case', () => {
+  const parser = new MdictParser('dict.mdx');
+  const insensitive = mddWithKeys(false, ['\\img\\cat.png']);
+  parser.mdd = insensitive;
+  parser.mdds = [insensitive];
+  SEPARATORS_AND_CASE.forEach(([name, key]) => {
+    assert.deepStrictEqual(parser.locateResource(name), key && Buffer.from(key), name);
+  });
+
+  const sensitive = mddWithKeys(true, ['\\img\\cat.png']);
+  parser.mdd = sensitive;
+  parser.mdds = [sensitive];
+  assert.deepStrictEqual(parser.locateResource('img/cat.png'), Buffer.from('\\img\\cat.png'));
+  assert.strictEqual(parser.locateResource('IMG/Cat.PNG'), null);
+});
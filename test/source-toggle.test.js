@@ -0,0 +1,57 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const SourceToggle = require('../src/source-toggle');
+
+// 记录每种来源被查询的次数，results 中没有的词返回 null
+function toggleWith(results) {
+  const calls = { local: 0, online: 0 };
+  const renderer = source => async (word) => {
+    calls[source]++;
+    return results[source][word] || null;
+  };
+  return { toggle: new SourceToggle({ local: renderer('local'), online: renderer('online') }), calls };
+}
+
+test('toggling from local returns the online rendering for the same word', async () => {
+  const { toggle } = toggleWith({ local: { hello: '<p>local</p>' }, online: { hello: '<p>online</p>' } });
+  toggle.remember('hello', 'local', '<p>local</p>');
+  assert.deepStrictEqual(await toggle.toggle('hello', 'local'), { source: 'online', html: '<p>online</p>', missing: null });
+});
+
+test('flipping back and forth uses the cached renderings', async () => {
+  const { toggle, calls } = toggleWith({ local: { hello: '<p>local</p>' }, online: { hello: '<p>online</p>' } });
+  toggle.remember('hello', 'local', '<p>local</p>');
+  await toggle.toggle('hello', 'local');
+  assert.strictEqual((await toggle.toggle('hello', 'online')).html, '<p>local</p>');
+  await toggle.toggle('hello', 'local');
+  assert.deepStrictEqual(calls, { local: 0, online: 1 });
+});
+
+test('stays on the available source when the other has no data', async () => {
+  const { toggle } = toggleWith({ local: { hello: '<p>local</p>' }, online: {} });
+  assert.deepStrictEqual(await toggle.toggle('hello', 'local'), { source: 'local', html: '<p>local</p>', missing: 'online' });
+});
+
+test('a failed online request counts as no data and is retried next time', async () => {
+  let fail = true;
+  const toggle = new SourceToggle({
+    local: async () => '<p>local</p>',
+    online: async () => {
+      if (fail) {
+        throw new Error('offline');
+      }
+      return '<p>online</p>';
+    }
+  });
+  assert.strictEqual((await toggle.toggle('hello', 'local')).missing, 'online');
+  fail = false;
+  assert.strictEqual((await toggle.toggle('hello', 'local')).source, 'online');
+});
+
+test('a new word discards the previous renderings', async () => {
+  const { toggle, calls } = toggleWith({ local: { a: 'A', b: 'B' }, online: { a: 'a', b: 'b' } });
+  await toggle.toggle('a', 'local');
+  toggle.remember('b', 'local', 'B');
+  assert.strictEqual((await toggle.toggle('b', 'local')).html, 'b');
+  assert.strictEqual(calls.online, 2);
+});
@@ -0,0 +1,78 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const MdictParser = require('../src/mdict-parser');
+
+// [HTML, 字节上限, 截断结果]
+const CASES = [
+  ['<p>apple pie</p>', 8, '<p>apple</p>'],
+  ['<p>a &amp; b</p>', 7, '<p>a </p>'],
+  ['<p>a &amp</p>', 8, '<p>a </p>'],
+  ['<p>a &#x4e2d;</p>', 9, '<p>a </p>'],
+  ['<p>fish & chips; peas</p>', 12, '<p>fish & ch</p>'],
+  ['<div><b>bold</b><i>it<br>alic</i></div>', 28, '<div><b>bold</b><i>it<br>ali</i></div>'],
+  ['<div><span class="x">text</span></div>', 12, '<div></div>'],
+  ['<p>one</p><p>two</p>', 10, '<p>one</p>']
+];
+
+test('truncateHtml does not cut tags or entities and closes open tags', () => {
+  const parser = new MdictParser('dict.mdx');
+  CASES.forEach(([html, maxBytes, expected]) => {
+    assert.strictEqual(parser.truncateHtml(html, maxBytes), expected, `${html} @ ${maxBytes}`);
+  });
+});
+
+test('a truncated entry is a balanced prefix of the full entry', async () => {
+  const definition = '<div class="entry">' +
+    Array.from({ length: 50 }, (_, i) => `<p><b>sense ${i}</b> &mdash; meaning &amp; use</p>`).join('') +
+    '</div>';
+  const parser = new MdictParser('dict.mdx');
+  parser.mdx = {
+    header: {},
+    keywordList: [{ keyText: 'word', index: 0 }],
+    lookup: word => ({ keyText: word, definition: word === 'word' ? definition : null }),
+    fetch_definition: () => ({ keyText: 'word', definition })
+  };
+  parser.updateDisplaySettings({ maxRenderBytes: 500 });
+
+  const truncated = (await parser.lookupSection('word')).html;
+  const full = parser.renderCurrentFull();
+  assert.match(truncated, /Show full entry/);
+  assert.doesNotMatch(full, /Show full entry/);
+
+  const body = truncated.slice(truncated.indexOf('<div class="entry">'), truncated.indexOf('<div class="show-full">'));
+  const fullBody = full.slice(full.indexOf('<div class="entry">'));
+  // 去掉末尾补上的结束标签后，截断的正文是完整正文的前缀
+  assert.ok(fullBody.startsWith(body.replace(/(<\/[a-z]+>)+$/, '')));
+  assert.strictEqual((body.match(/<p>/g) || []).length, (body.match(/<\/p>/g) || []).length);
+  assert.strictEqual((body.match(/<div/g) || []).length, (body.match(/<\/div>/g) || []).length);
+  assert.ok(Buffer.byteLength(body) < Buffer.byteLength(fullBody));
+});
+
+// [文本, 字节上限, 截断结果]
+const UTF8_CASES = [
+  ['你好世界', 4, '你'],
+  ['你好世界', 6, '你好'],
+  ['你好世界', 2, ''],
+  ['café crème', 4, 'caf'],
+  ['café crème', 5, 'café'],
+  ['😀a', 3, ''],
+  ['😀a', 4, '😀'],
+  ['abc', 10, 'abc'],
+  ['abc', -1, '']
+];
+
+test('truncateUtf8 cuts on character boundaries for CJK, accented and astral text', () => {
+  UTF8_CASES.forEach(([text, maxBytes, expected]) => {
+    assert.strictEqual(MdictParser.truncateUtf8(text, maxBytes), expected, `${text} @ ${maxBytes}`);
+  });
+  assert.strictEqual(new MdictParser('dict.mdx').truncateHtml('<p>你好世界</p>', 10), '<p>你好</p>');
+});
+
+test('briefs of CJK and accented definitions are cut by characters, not bytes', () => {
+  const parser = new MdictParser('dict.mdx');
+  parser.lookupText = word => ({ zh: '这是一个很长的中文释义，用来测试截断', fr: 'crème brûlée à la française' })[word];
+  parser.updateSearchOptions({ briefLength: 5, briefFirstSentence: false });
+
+  assert.strictEqual(parser.getBrief('zh'), '这是一个很…');
+  assert.strictEqual(parser.getBrief('fr'), 'crème…');
+});